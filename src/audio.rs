@@ -0,0 +1,78 @@
+use crate::*;
+use ::rand::prelude::*;
+use macroquad::audio::{self, Sound, PlaySoundParams};
+
+// ==================== 音效子系统 ====================
+
+/// 按事件分类持有多条音源的音效池；每次播放从池里随机挑一条，
+/// 这样连续触发同一类事件（比如连杀）不会每次都是完全相同的音色。
+/// 池为空（未配置或加载失败）时播放直接什么都不做，不需要在调用点额外判断
+pub struct AudioManager {
+    pub machinegun_fire: Vec<Sound>,
+    pub laser_fire: Vec<Sound>,
+    pub shotgun_fire: Vec<Sound>,
+    pub hit: Vec<Sound>,
+    pub enemy_death: Vec<Sound>,
+    pub boss_death: Vec<Sound>,
+    pub item_pickup: Vec<Sound>,
+    pub level_up: Vec<Sound>,
+    pub rogue_select: Vec<Sound>,
+}
+
+impl AudioManager {
+    pub fn new() -> Self {
+        Self {
+            machinegun_fire: Vec::new(),
+            laser_fire: Vec::new(),
+            shotgun_fire: Vec::new(),
+            hit: Vec::new(),
+            enemy_death: Vec::new(),
+            boss_death: Vec::new(),
+            item_pickup: Vec::new(),
+            level_up: Vec::new(),
+            rogue_select: Vec::new(),
+        }
+    }
+
+    pub fn weapon_fire_pool(&self, weapon_type: &WeaponType) -> &[Sound] {
+        match weapon_type {
+            WeaponType::MachineGun => &self.machinegun_fire,
+            WeaponType::Laser => &self.laser_fire,
+            WeaponType::Shotgun => &self.shotgun_fire,
+        }
+    }
+}
+
+/// 从分类音效池里随机挑一条播放；池为空时什么都不做
+pub fn play_pooled(pool: &[Sound], volume: f32, rng: &mut ThreadRng) {
+    if pool.is_empty() {
+        return;
+    }
+    let index = rng.gen_range(0..pool.len());
+    audio::play_sound(&pool[index], PlaySoundParams { looped: false, volume });
+}
+
+/// 依次加载一组候选音源，跳过加载失败的，凑成一个可以随机挑选的音效池
+pub async fn load_sound_pool(paths: &[&str], name: &str) -> Vec<Sound> {
+    let mut pool = Vec::new();
+    for path in paths {
+        if let Some(sound) = load_game_sound(path, name).await {
+            pool.push(sound);
+        }
+    }
+    pool
+}
+
+impl Game {
+    pub fn play_weapon_fire_sound(&mut self, weapon_type: &WeaponType) {
+        play_pooled(self.audio.weapon_fire_pool(weapon_type), self.master_volume, &mut self.rng);
+    }
+
+    pub fn play_level_up_sound(&mut self) {
+        play_pooled(&self.audio.level_up, self.master_volume, &mut self.rng);
+    }
+
+    pub fn play_rogue_select_sound(&mut self) {
+        play_pooled(&self.audio.rogue_select, self.master_volume, &mut self.rng);
+    }
+}
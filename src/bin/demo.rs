@@ -0,0 +1,191 @@
+use macroquad::prelude::*;
+
+// 这些子系统模块的源文件放在`src/`下（主程序src/main.rs并不引用它们），是把这个原型独立成
+// `src/bin/demo.rs`而不是合并进主游戏循环时唯一需要特殊处理的地方：二进制
+// 各自是独立的crate根，默认会去`src/bin/`下找同名模块文件，所以要显式`#[path]`
+// 指回`src/`里那份实现，而不是复制一份。
+#[path = "../camera.rs"] mod camera;
+#[path = "../fov.rs"] mod fov;
+#[path = "../mapgen.rs"] mod mapgen;
+#[path = "../pathfinding.rs"] mod pathfinding;
+#[path = "../render.rs"] mod render;
+#[path = "../turns.rs"] mod turns;
+use camera::GameCamera;
+use fov::{compute_fov, FovMap, TileVisibility};
+use mapgen::{generate_map, Tile};
+use pathfinding::astar;
+use render::TileAtlas;
+use turns::{Actor, Scheduler, ACTION_COST};
+
+const MAP_WIDTH: i32 = 40;
+const MAP_HEIGHT: i32 = 30;
+const TILE_PX: f32 = 20.0;
+
+const PLAYER_ID: usize = 0;
+const ENEMY_ID: usize = 1;
+
+#[macroquad::main("Roguelike")]
+async fn main() {
+    let seed = 42;
+    let map = generate_map(MAP_WIDTH, MAP_HEIGHT, seed);
+    let mut fov_map = FovMap::new(MAP_WIDTH, MAP_HEIGHT);
+    // 若图块集加载失败，回退到原来的矩形/圆形绘制
+    let atlas = TileAtlas::load("resources/tileset.png", 16.0).await;
+
+    // 玩家和一只会追击的敌人各自出生在不同的房间
+    let mut player_tile = map.rooms.first().map(|r| r.center()).unwrap_or((1, 1));
+    let mut enemy_tile = map.rooms.get(1).map(|r| r.center());
+    // 敌人最后一次看到玩家的位置；不在视野内时朝这里走，走到了就四处游荡
+    let mut enemy_last_known_player: Option<(i32, i32)> = None;
+
+    // 玩家速度为基准100，敌人速度120，因此敌人每几回合能多行动一次
+    let mut scheduler = Scheduler::new();
+    scheduler.add_actor(Actor::new(PLAYER_ID, 100, true));
+    if enemy_tile.is_some() {
+        scheduler.add_actor(Actor::new(ENEMY_ID, 120, false));
+    }
+
+    // 地牢比窗口大得多，所以用一个跟随玩家的摄像机代替固定视口
+    let mut game_camera = GameCamera::new(screen_width(), screen_height(), MAP_WIDTH as f32 * TILE_PX, MAP_HEIGHT as f32 * TILE_PX);
+
+    loop {
+        clear_background(BLACK);
+
+        let is_opaque = |x: i32, y: i32| map.is_opaque(x, y);
+        let visible = compute_fov(player_tile, 6, is_opaque);
+        fov_map.update(&visible);
+
+        let player_pixel = macroquad::math::Vec2::new(
+            player_tile.0 as f32 * TILE_PX + TILE_PX / 2.0,
+            player_tile.1 as f32 * TILE_PX + TILE_PX / 2.0,
+        );
+        game_camera.follow(player_pixel);
+        game_camera.activate();
+
+        // 绘制地图格子，依据可见性状态决定显示/变暗/隐藏
+        for y in 0..MAP_HEIGHT {
+            for x in 0..MAP_WIDTH {
+                let state = fov_map.get(x, y);
+                if state == TileVisibility::Unseen {
+                    continue;
+                }
+                let base_color = match map.get(x, y) {
+                    Tile::Wall => GRAY,
+                    Tile::Floor => DARKGRAY,
+                };
+                let tint = match state {
+                    TileVisibility::Visible => WHITE,
+                    TileVisibility::Explored => Color::new(0.4, 0.4, 0.4, 1.0),
+                    TileVisibility::Unseen => unreachable!(),
+                };
+
+                if let Some(atlas) = &atlas {
+                    let glyph = match map.get(x, y) {
+                        Tile::Wall => "wall",
+                        Tile::Floor => "floor",
+                    };
+                    atlas.draw_tile(glyph, x as f32 * TILE_PX, y as f32 * TILE_PX, TILE_PX, tint);
+                } else {
+                    let color = Color::new(base_color.r * tint.r, base_color.g * tint.g, base_color.b * tint.b, 1.0);
+                    draw_rectangle(x as f32 * TILE_PX, y as f32 * TILE_PX, TILE_PX - 1.0, TILE_PX - 1.0, color);
+                }
+            }
+        }
+
+        // 绘制敌人（若在视野内）
+        if let Some(enemy_pos) = enemy_tile {
+            if fov_map.get(enemy_pos.0, enemy_pos.1) == TileVisibility::Visible {
+                if let Some(atlas) = &atlas {
+                    atlas.draw_tile("enemy_scout", enemy_pos.0 as f32 * TILE_PX, enemy_pos.1 as f32 * TILE_PX, TILE_PX, WHITE);
+                } else {
+                    draw_circle(enemy_pos.0 as f32 * TILE_PX + TILE_PX / 2.0, enemy_pos.1 as f32 * TILE_PX + TILE_PX / 2.0, 8.0, RED);
+                }
+            }
+        }
+
+        // 绘制玩家
+        if let Some(atlas) = &atlas {
+            atlas.draw_tile("player", player_tile.0 as f32 * TILE_PX, player_tile.1 as f32 * TILE_PX, TILE_PX, WHITE);
+        } else {
+            draw_circle(player_tile.0 as f32 * TILE_PX + TILE_PX / 2.0, player_tile.1 as f32 * TILE_PX + TILE_PX / 2.0, 8.0, BLUE);
+        }
+
+        let try_move = |pos: (i32, i32), dx: i32, dy: i32| -> (i32, i32) {
+            let next = (pos.0 + dx, pos.1 + dy);
+            if map.get(next.0, next.1) == Tile::Floor {
+                next
+            } else {
+                pos
+            }
+        };
+
+        // 回合制输入：玩家的移动键即为"行动"，只有当这次世界tick让玩家攒够能量
+        // 并花掉之后，才会继续推进tick，给敌人机会行动。
+        let player_move = if is_key_pressed(KeyCode::W) {
+            Some((0, -1))
+        } else if is_key_pressed(KeyCode::S) {
+            Some((0, 1))
+        } else if is_key_pressed(KeyCode::A) {
+            Some((-1, 0))
+        } else if is_key_pressed(KeyCode::D) {
+            Some((1, 0))
+        } else {
+            None
+        };
+
+        if let Some((dx, dy)) = player_move {
+            // 不断推进世界tick，直到玩家的能量攒够可以行动
+            while !scheduler.actors.iter().any(|a| a.id == PLAYER_ID && a.can_act()) {
+                scheduler.tick();
+            }
+            player_tile = try_move(player_tile, dx, dy);
+            scheduler.spend(PLAYER_ID, ACTION_COST);
+
+            // 玩家花完这次行动的能量后，让所有攒够能量的NPC依次行动
+            while let Some(id) = scheduler.next_ready() {
+                if id == PLAYER_ID {
+                    break;
+                }
+                if id == ENEMY_ID {
+                    if let Some(pos) = enemy_tile {
+                        // 玩家进入敌人视野后更新最后已知位置，否则继续前往上一次看到的地方
+                        let enemy_sees_player = compute_fov(pos, 8, |x, y| map.is_opaque(x, y)).contains(&player_tile);
+                        if enemy_sees_player {
+                            enemy_last_known_player = Some(player_tile);
+                        }
+
+                        if let Some(target) = enemy_last_known_player {
+                            if target == pos {
+                                enemy_last_known_player = None;
+                            } else if let Some(path) = astar(pos, target, |x, y| map.is_opaque(x, y)) {
+                                if let Some(&next_step) = path.get(1) {
+                                    enemy_tile = Some(next_step);
+                                }
+                            }
+                        }
+                    }
+                }
+                scheduler.spend(id, ACTION_COST);
+            }
+        }
+
+        // HUD用固定屏幕空间摄像机绘制，不随世界滚动
+        game_camera.deactivate();
+
+        draw_text("Roguelike Test", 10.0, 20.0, 24.0, WHITE);
+        draw_text("WASD to move - fog of war reveals as you explore", 10.0, 40.0, 16.0, LIGHTGRAY);
+
+        // 鼠标悬停的格子名称提示
+        let mouse_world = game_camera.screen_to_world(macroquad::math::Vec2::from(mouse_position()));
+        let hovered_tile = ((mouse_world.x / TILE_PX).floor() as i32, (mouse_world.y / TILE_PX).floor() as i32);
+        if fov_map.get(hovered_tile.0, hovered_tile.1) != TileVisibility::Unseen {
+            let tile_name = match map.get(hovered_tile.0, hovered_tile.1) {
+                Tile::Wall => "Wall",
+                Tile::Floor => "Floor",
+            };
+            draw_text(tile_name, 10.0, 60.0, 16.0, YELLOW);
+        }
+
+        next_frame().await
+    }
+}
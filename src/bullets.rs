@@ -0,0 +1,881 @@
+use crate::*;
+
+// ==================== 子弹系统 ====================
+
+/// 子弹类型
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulletType {
+    PlayerMachineGun,
+    PlayerLaser,
+    PlayerShotgun,
+    PlayerHoming,
+    EnemyHeavy,
+    EnemyBoss,
+    EnemyGeneric,
+}
+
+/// 子弹逐帧行为，参照doukutsu-rs的`tick_snake_1`等按子弹类型分派的per-frame逻辑
+#[derive(Debug, Clone, Copy)]
+pub enum BulletBehavior {
+    /// 维持`update_bullets`里原有的匀速直线运动，不做任何额外处理
+    None,
+    /// 每帧转向最近的目标，转向角速度不超过`turn_rate`（弧度/秒），并以`accel`朝`base_speed`这一速度上限加速
+    Homing { turn_rate: f32, accel: f32 },
+    /// 沿`base_heading`匀速前进，同时叠加一个由`age`驱动、垂直于前进方向的正弦摆动
+    Snake { amplitude: f32, frequency: f32 },
+}
+
+/// 子弹结构
+#[derive(Debug, Clone)]
+pub struct Bullet {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub damage: i32,
+    pub is_player_bullet: bool,
+    pub piercing_count: i32,
+    pub ricochet_count: i32,
+    pub burning_damage: i32,
+    pub explosion_damage: f32,
+    pub is_crit: bool,
+    pub hit_enemies: Vec<usize>,
+    pub bullet_type: BulletType,
+    pub element: Element,
+    pub direction: f32,
+    pub speed: f32,
+    pub dir_interp: Option<Interp>,
+    pub speed_interp: Option<Interp>,
+    pub accel: Vec2,
+    pub accel_remaining: i32,
+    pub behavior: BulletBehavior,
+    pub behavior_age: f32,
+    pub base_heading: f32,
+    pub base_speed: f32,
+    /// 剩余存活帧数，归零时无论是否仍在屏幕内都会消失；默认给满`u16::MAX`，同样逐帧递减，
+    /// 约65535帧后才到期
+    pub life_frames: u16,
+    /// 每秒叠加到`velocity.y`上的重力加速度，0表示不受重力影响
+    pub gravity: f32,
+    /// `gravity`作用下`velocity.y`的上限
+    pub max_fall_speed: f32,
+    /// 该子弹专属的伪随机数发生器，由`BulletManager`在入队时从`seeder`派生，
+    /// 使抖动/蛇形相位/重新索敌这类随机行为可以从单一的对局种子中重放
+    pub rng: Xoroshiro32PlusPlus,
+}
+
+impl Bullet {
+    pub fn new(position: Vec2, velocity: Vec2, damage: i32, is_player_bullet: bool, bullet_type: BulletType) -> Self {
+        Self {
+            position,
+            velocity,
+            damage,
+            is_player_bullet,
+            piercing_count: 0,
+            ricochet_count: 0,
+            burning_damage: 0,
+            explosion_damage: 0.0,
+            is_crit: false,
+            hit_enemies: Vec::new(),
+            bullet_type,
+            element: Element::Neutral,
+            direction: 0.0,
+            speed: 0.0,
+            dir_interp: None,
+            speed_interp: None,
+            accel: Vec2::new(0.0, 0.0),
+            accel_remaining: 0,
+            behavior: BulletBehavior::None,
+            behavior_age: 0.0,
+            base_heading: velocity.y.atan2(velocity.x),
+            base_speed: velocity.length(),
+            life_frames: u16::MAX,
+            gravity: 0.0,
+            max_fall_speed: 6.0,
+            // 占位种子，真正用于对局回放的种子由`BulletManager::create_bullet`重新派生
+            rng: Xoroshiro32PlusPlus::new(0),
+        }
+    }
+
+    /// 按`ChangeDirection`/`ChangeSpeed`/`Accel`注册的插值推进一帧：
+    /// 朝向/速度线性过渡到目标值后重新合成速度向量，匀加速度则直接叠加到速度上
+    pub fn tick_pattern_motion(&mut self) {
+        let mut velocity_dirty = false;
+
+        if let Some(interp) = &mut self.dir_interp {
+            interp.remaining -= 1;
+            if interp.remaining <= 0 {
+                self.direction = interp.target;
+                self.dir_interp = None;
+            } else {
+                self.direction += (interp.target - self.direction) / (interp.remaining + 1) as f32;
+            }
+            velocity_dirty = true;
+        }
+
+        if let Some(interp) = &mut self.speed_interp {
+            interp.remaining -= 1;
+            if interp.remaining <= 0 {
+                self.speed = interp.target;
+                self.speed_interp = None;
+            } else {
+                self.speed += (interp.target - self.speed) / (interp.remaining + 1) as f32;
+            }
+            velocity_dirty = true;
+        }
+
+        if velocity_dirty {
+            let rad = self.direction.to_radians();
+            self.velocity = Vec2::new(rad.cos() * self.speed, rad.sin() * self.speed);
+        }
+
+        if self.accel_remaining > 0 {
+            self.velocity.x += self.accel.x;
+            self.velocity.y += self.accel.y;
+            self.accel_remaining -= 1;
+        }
+    }
+
+    /// 在`targets`中找出离`position`最近的一个（玩家子弹传敌人位置列表，敌人子弹传玩家位置）
+    fn nearest_target(position: Vec2, targets: &[Vec2]) -> Option<Vec2> {
+        targets
+            .iter()
+            .copied()
+            .min_by(|a, b| position.distance(a).partial_cmp(&position.distance(b)).unwrap())
+    }
+
+    /// 按`behavior`推进一帧速度：`Homing`追踪`targets`中最近的目标，`Snake`叠加正弦摆动
+    pub fn tick_behavior(&mut self, targets: &[Vec2], dt: f32) {
+        match self.behavior {
+            BulletBehavior::None => {},
+            BulletBehavior::Homing { turn_rate, accel } => {
+                let Some(target) = Self::nearest_target(self.position, targets) else { return };
+
+                // 瞄准角叠加一点随子弹自身种子重放的抖动，避免一大群追踪弹完全贴在同一条轨迹上
+                let jitter = self.rng.range(-0.05, 0.05);
+                let desired = (target.y - self.position.y).atan2(target.x - self.position.x) + jitter;
+                let current = self.velocity.y.atan2(self.velocity.x);
+                let mut delta = desired - current;
+                while delta > std::f32::consts::PI {
+                    delta -= std::f32::consts::TAU;
+                }
+                while delta < -std::f32::consts::PI {
+                    delta += std::f32::consts::TAU;
+                }
+
+                let max_turn = turn_rate * dt;
+                let new_heading = current + delta.clamp(-max_turn, max_turn);
+                let new_speed = (self.velocity.length() + accel * dt).min(self.base_speed);
+
+                self.velocity = Vec2::new(new_heading.cos() * new_speed, new_heading.sin() * new_speed);
+            },
+            BulletBehavior::Snake { amplitude, frequency } => {
+                self.behavior_age += dt;
+
+                let forward = Vec2::new(self.base_heading.cos(), self.base_heading.sin());
+                let perpendicular = self.base_heading + std::f32::consts::FRAC_PI_2;
+                let wiggle = (self.behavior_age * frequency).sin() * amplitude;
+
+                self.velocity = Vec2::new(
+                    forward.x * self.base_speed + perpendicular.cos() * wiggle,
+                    forward.y * self.base_speed + perpendicular.sin() * wiggle,
+                );
+            },
+        }
+    }
+}
+
+// ==================== 弹幕脚本系统（BulletML风格）====================
+//
+// 借鉴BulletML/BulletMLRunner的设计：把敌人的攻击节奏写成一棵动作树，
+// 而不是在`update_enemies`里手写一长串match分支。`PatternRunner`每帧调用一次
+// `step`，`Wait`的单位是"帧"（即每次调用`step`算一帧，而非现实秒数）。
+
+/// 朝向/速度取值的参照系
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueMode {
+    /// 直接使用给定的绝对角度/速度
+    Absolute,
+    /// 在"正前方"（竖直向下）基础上偏移，用于不瞄准玩家的固定扇形
+    Relative,
+    /// 朝向玩家方向偏移，实现追踪弹
+    Aim,
+    /// 在上一次Fire实际使用的值基础上累加，用于螺旋弹幕
+    Sequence,
+}
+
+/// 方向取值
+#[derive(Debug, Clone, Copy)]
+pub struct DirSpec {
+    pub mode: ValueMode,
+    pub value: f32,
+}
+
+/// 速度取值
+#[derive(Debug, Clone, Copy)]
+pub struct SpeedSpec {
+    pub mode: ValueMode,
+    pub value: f32,
+}
+
+/// 某个子弹朝向/速度向目标值线性过渡所剩的帧数
+#[derive(Debug, Clone, Copy)]
+pub struct Interp {
+    pub target: f32,
+    pub remaining: i32,
+}
+
+/// 弹幕脚本的一个动作节点
+#[derive(Debug, Clone)]
+pub enum PatternAction {
+    /// 按当前朝向/速度发射一颗子弹；伤害由调用方统一传入，不写进脚本数据
+    Fire { dir: DirSpec, speed: SpeedSpec, bullet_type: BulletType },
+    /// 等待指定帧数后再继续执行
+    Wait(i32),
+    /// 把`body`重复执行`times`次
+    Repeat { times: i32, body: Vec<PatternAction> },
+    /// 让本帧新发射的子弹在`term`帧内把朝向线性过渡到`target`
+    ChangeDirection { target: DirSpec, term: i32 },
+    /// 让本帧新发射的子弹在`term`帧内把速度线性过渡到`target`
+    ChangeSpeed { target: SpeedSpec, term: i32 },
+    /// 给本帧新发射的子弹叠加`term`帧的匀加速度`(h, v)`
+    Accel { h: f32, v: f32, term: i32 },
+    /// 令运行该脚本的敌人消失（简化为直接清空其生命值）
+    Vanish,
+    /// 切换运行该脚本的敌人的无敌状态，用于实现"等待+取消无敌"式的无敌窗口
+    SetInvincible(bool),
+}
+
+/// `step`执行完一帧脚本后，需要由脚本的持有者（敌人/Boss）处理的副作用
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PatternSignal {
+    pub vanish: bool,
+    pub set_invincible: Option<bool>,
+}
+
+/// 执行栈的一层：指向当前动作列表中的下标，以及若该层是`Repeat`的循环体，剩余的循环次数
+#[derive(Debug, Clone, Copy)]
+struct RunnerFrame {
+    index: usize,
+    repeat_remaining: i32,
+}
+
+/// 弹幕脚本的执行状态：一个栈（用于进出`Repeat`的循环体）、一个等待计时器，
+/// 以及`Sequence`模式需要参照的"上一次Fire实际使用的朝向/速度"
+#[derive(Debug, Clone)]
+pub struct PatternRunner {
+    stack: Vec<RunnerFrame>,
+    wait_timer: i32,
+    last_direction: f32,
+    last_speed: f32,
+    finished: bool,
+}
+
+impl PatternRunner {
+    pub fn new() -> Self {
+        Self {
+            stack: vec![RunnerFrame { index: 0, repeat_remaining: 0 }],
+            wait_timer: 0,
+            last_direction: 90.0,
+            last_speed: 1.0,
+            finished: false,
+        }
+    }
+
+    /// 沿当前栈深入嵌套的`Repeat`循环体，找到最内层正在执行的动作列表
+    fn resolve_action_list<'a>(root: &'a [PatternAction], stack: &[RunnerFrame]) -> &'a [PatternAction] {
+        let mut list = root;
+        for frame in &stack[..stack.len() - 1] {
+            if let PatternAction::Repeat { body, .. } = &list[frame.index] {
+                list = body;
+            }
+        }
+        list
+    }
+
+    fn resolve_dir(&self, spec: DirSpec, origin: Vec2, player_pos: Vec2) -> f32 {
+        match spec.mode {
+            ValueMode::Absolute => spec.value,
+            ValueMode::Relative => 90.0 + spec.value,
+            ValueMode::Aim => {
+                let dir = Vec2::new(player_pos.x - origin.x, player_pos.y - origin.y).normalize();
+                dir.y.atan2(dir.x).to_degrees() + spec.value
+            },
+            ValueMode::Sequence => self.last_direction + spec.value,
+        }
+    }
+
+    fn resolve_speed(&self, spec: SpeedSpec) -> f32 {
+        match spec.mode {
+            ValueMode::Absolute => spec.value,
+            ValueMode::Relative => 1.0 + spec.value,
+            ValueMode::Aim => self.last_speed,
+            ValueMode::Sequence => self.last_speed + spec.value,
+        }
+    }
+
+    /// 给本帧最近一批发射的子弹（`emitted[since..]`）挂上朝向插值
+    fn apply_direction_interp(emitted: &mut [Bullet], since: usize, target: f32, term: i32) {
+        if term <= 0 {
+            return;
+        }
+        for bullet in &mut emitted[since.min(emitted.len())..] {
+            bullet.dir_interp = Some(Interp { target, remaining: term });
+        }
+    }
+
+    /// 给本帧最近一批发射的子弹挂上速度插值
+    fn apply_speed_interp(emitted: &mut [Bullet], since: usize, target: f32, term: i32) {
+        if term <= 0 {
+            return;
+        }
+        for bullet in &mut emitted[since.min(emitted.len())..] {
+            bullet.speed_interp = Some(Interp { target, remaining: term });
+        }
+    }
+
+    /// 给本帧最近一批发射的子弹挂上匀加速度
+    fn apply_accel(emitted: &mut [Bullet], since: usize, h: f32, v: f32, term: i32) {
+        if term <= 0 {
+            return;
+        }
+        for bullet in &mut emitted[since.min(emitted.len())..] {
+            bullet.accel = Vec2::new(h, v);
+            bullet.accel_remaining = term;
+        }
+    }
+
+    /// 推进一帧：`wait_timer`未归零时只是倒计时；归零后按顺序执行动作，
+    /// 直到遇到下一个`Wait`（或脚本自然结束）为止，沿途的`Fire`被追加到`emitted`
+    pub fn step(&mut self, root: &[PatternAction], origin: Vec2, player_pos: Vec2, damage: i32, emitted: &mut Vec<Bullet>) -> PatternSignal {
+        let mut signal = PatternSignal::default();
+
+        if self.finished {
+            return signal;
+        }
+
+        if self.wait_timer > 0 {
+            self.wait_timer -= 1;
+            return signal;
+        }
+
+        let mut fired_marker = emitted.len();
+        let mut guard = 0u32;
+
+        loop {
+            guard += 1;
+            if guard > 100_000 {
+                // 数据里出现了没有Wait/Fire的死循环（例如空的Repeat体），避免卡死主循环
+                self.finished = true;
+                break;
+            }
+
+            let Some(frame_index) = self.stack.len().checked_sub(1) else {
+                self.finished = true;
+                break;
+            };
+
+            let list = Self::resolve_action_list(root, &self.stack);
+            let idx = self.stack[frame_index].index;
+
+            if idx >= list.len() {
+                let repeat_remaining = self.stack[frame_index].repeat_remaining;
+                if repeat_remaining > 1 {
+                    self.stack[frame_index].index = 0;
+                    self.stack[frame_index].repeat_remaining -= 1;
+                } else {
+                    self.stack.pop();
+                    if let Some(parent) = self.stack.last_mut() {
+                        parent.index += 1;
+                    } else {
+                        self.finished = true;
+                        break;
+                    }
+                }
+                continue;
+            }
+
+            match &list[idx] {
+                PatternAction::Fire { dir, speed, bullet_type } => {
+                    let direction = self.resolve_dir(*dir, origin, player_pos);
+                    let speed_val = self.resolve_speed(*speed);
+                    self.last_direction = direction;
+                    self.last_speed = speed_val;
+
+                    let rad = direction.to_radians();
+                    let velocity = Vec2::new(rad.cos() * speed_val, rad.sin() * speed_val);
+                    let mut bullet = Bullet::new(origin, velocity, damage, false, bullet_type.clone());
+                    bullet.direction = direction;
+                    bullet.speed = speed_val;
+                    emitted.push(bullet);
+
+                    fired_marker = emitted.len();
+                    self.stack[frame_index].index += 1;
+                },
+                PatternAction::Wait(frames) => {
+                    self.wait_timer = *frames;
+                    self.stack[frame_index].index += 1;
+                    break;
+                },
+                PatternAction::Repeat { times, .. } => {
+                    if *times <= 0 {
+                        self.stack[frame_index].index += 1;
+                    } else {
+                        self.stack.push(RunnerFrame { index: 0, repeat_remaining: *times });
+                    }
+                },
+                PatternAction::ChangeDirection { target, term } => {
+                    let target_deg = self.resolve_dir(*target, origin, player_pos);
+                    Self::apply_direction_interp(emitted, fired_marker, target_deg, *term);
+                    self.stack[frame_index].index += 1;
+                },
+                PatternAction::ChangeSpeed { target, term } => {
+                    let target_speed = self.resolve_speed(*target);
+                    Self::apply_speed_interp(emitted, fired_marker, target_speed, *term);
+                    self.stack[frame_index].index += 1;
+                },
+                PatternAction::Accel { h, v, term } => {
+                    Self::apply_accel(emitted, fired_marker, *h, *v, *term);
+                    self.stack[frame_index].index += 1;
+                },
+                PatternAction::Vanish => {
+                    signal.vanish = true;
+                    self.stack[frame_index].index += 1;
+                },
+                PatternAction::SetInvincible(value) => {
+                    signal.set_invincible = Some(*value);
+                    self.stack[frame_index].index += 1;
+                },
+            }
+        }
+
+        signal
+    }
+}
+
+/// 弹幕瞄准方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AimMode {
+    /// 固定朝向`launch_angle`（标准数学夹角：0°指向右，90°指向下）
+    Fixed,
+    /// 以发射点指向玩家的方向为扇形中心线
+    AtPlayer,
+    /// 忽略`launch_angle`/`angle_spread`，均匀环绕一整圈
+    Ring,
+}
+
+/// 数据化弹幕发射器，借鉴东方系ECL脚本的`SetBulletAttributes`思路：
+/// 把"扇形齐射"、"环形弹幕"这类攻击描述成一组参数，而不是手写循环
+#[derive(Debug, Clone, Copy)]
+pub struct BulletEmitter {
+    pub bullets_per_shot: i32,
+    pub number_of_shots: i32,
+    pub base_speed: f32,
+    pub speed_delta: f32,
+    pub launch_angle: f32,
+    pub angle_spread: f32,
+    pub aim_mode: AimMode,
+}
+
+impl BulletEmitter {
+    pub fn new(bullets_per_shot: i32, number_of_shots: i32, base_speed: f32, speed_delta: f32, launch_angle: f32, angle_spread: f32, aim_mode: AimMode) -> Self {
+        Self {
+            bullets_per_shot,
+            number_of_shots,
+            base_speed,
+            speed_delta,
+            launch_angle,
+            angle_spread,
+            aim_mode,
+        }
+    }
+
+    /// 从`origin`发射全部弹幕：每一轮`number_of_shots`把`bullets_per_shot`颗子弹
+    /// 均匀铺满`angle_spread`，轮次之间按`speed_delta`递增弹速，形成扩散的弹幕环
+    pub fn emit(&self, origin: Vec2, player_pos: Vec2, damage: i32, bullet_type: BulletType) -> Vec<Bullet> {
+        let is_ring = self.aim_mode == AimMode::Ring;
+
+        let center_angle = match self.aim_mode {
+            AimMode::Fixed => self.launch_angle,
+            AimMode::AtPlayer => {
+                let dir = Vec2::new(player_pos.x - origin.x, player_pos.y - origin.y).normalize();
+                dir.y.atan2(dir.x).to_degrees()
+            },
+            AimMode::Ring => 0.0,
+        };
+
+        let spread = if is_ring { 360.0 } else { self.angle_spread };
+        // 环形首尾相接，按n等分；扇形两端都要落在边缘上，按n-1等分才能以中心线对称
+        let angle_step = if self.bullets_per_shot <= 1 {
+            0.0
+        } else if is_ring {
+            spread / self.bullets_per_shot as f32
+        } else {
+            spread / (self.bullets_per_shot - 1) as f32
+        };
+        let start_angle = if is_ring { 0.0 } else { center_angle - spread / 2.0 };
+
+        let mut bullets = Vec::with_capacity((self.bullets_per_shot * self.number_of_shots).max(0) as usize);
+        for shot in 0..self.number_of_shots {
+            let speed = self.base_speed + self.speed_delta * shot as f32;
+            for i in 0..self.bullets_per_shot {
+                let angle = (start_angle + angle_step * i as f32).to_radians();
+                let velocity = Vec2::new(angle.cos() * speed, angle.sin() * speed);
+                bullets.push(Bullet::new(origin, velocity, damage, false, bullet_type.clone()));
+            }
+        }
+
+        bullets
+    }
+}
+
+// ==================== 子弹管理系统（BulletManager）====================
+//
+// 把子弹`Vec`和驱动其随机行为的RNG收拢到一个对象里：`BulletManager`持有正式的
+// `bullets`、一个`new_bullets`暂存区（本帧新开火/新生成的子弹先进这里，`tick`时统一并入），
+// 以及一个`XorShiftSeeder`。每当子弹入队，都会从`seeder`派下一个新种子喂给它自己的
+// `Xoroshiro32PlusPlus`，这样抖动/蛇形相位/索敌重新瞄准这些随子弹而异的随机性，
+// 只要固定对局的起始种子就能完全重放，不再和全局`ThreadRng`的抽取顺序绑死。
+
+/// 一个小型xorshift，只用来给每颗子弹派生互不相关的种子，本身不直接参与战斗随机数
+pub struct XorShiftSeeder {
+    state: u32,
+}
+
+impl XorShiftSeeder {
+    pub fn new(seed: u32) -> Self {
+        Self { state: if seed == 0 { 0x9E37_79B9 } else { seed } }
+    }
+
+    pub fn next_seed(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+}
+
+/// 挂在每颗子弹上的小型PRNG，状态只有4字节，足够支撑子弹一生的随机决策并可从种子完整重放
+#[derive(Debug, Clone, Copy)]
+pub struct Xoroshiro32PlusPlus {
+    s0: u16,
+    s1: u16,
+}
+
+impl Xoroshiro32PlusPlus {
+    pub fn new(seed: u32) -> Self {
+        let s0 = (seed >> 16) as u16;
+        let s1 = seed as u16;
+        let mut rng = Self {
+            s0: if s0 == 0 { 0xACE1 } else { s0 },
+            s1: if s1 == 0 { 0x1234 } else { s1 },
+        };
+        for _ in 0..4 {
+            rng.next_u16();
+        }
+        rng
+    }
+
+    pub fn next_u16(&mut self) -> u16 {
+        let s0 = self.s0;
+        let mut s1 = self.s1;
+        let result = s0.wrapping_add(s1).rotate_left(9).wrapping_add(s0);
+
+        s1 ^= s0;
+        self.s0 = s0.rotate_left(13) ^ s1 ^ (s1 << 5);
+        self.s1 = s1.rotate_left(10);
+
+        result
+    }
+
+    /// `[0.0, 1.0)`区间的浮点数
+    pub fn next_f32(&mut self) -> f32 {
+        self.next_u16() as f32 / (u16::MAX as f32 + 1.0)
+    }
+
+    /// `[min, max)`区间的浮点数
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + self.next_f32() * (max - min)
+    }
+}
+
+/// 子弹命中敌人后的结算信息：检测与子弹自身状态的更新（穿透/命中列表/移除）已经由
+/// `BulletManager::tick`就地完成，敌人一侧的生效（扣血/掉落/击杀统计）交还给`Game`，
+/// 因为敌人列表并不归`BulletManager`所有
+pub struct BulletHitReport {
+    pub enemy_hits: Vec<(usize, i32, Element, bool)>,
+    pub enemy_burning_applies: Vec<(usize, StatusEffect)>,
+    pub explosion_damages: Vec<(Vec2, i32, Element)>,
+}
+
+/// 拥有子弹存储、暂存区和种子发生器的子弹子系统
+pub struct BulletManager {
+    pub bullets: Vec<Bullet>,
+    pub new_bullets: Vec<Bullet>,
+    seeder: XorShiftSeeder,
+}
+
+impl BulletManager {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            bullets: Vec::new(),
+            new_bullets: Vec::new(),
+            seeder: XorShiftSeeder::new(seed),
+        }
+    }
+
+    /// 构造一颗子弹并立刻从`seeder`派生出它自己的PRNG，供调用方在入队前计算抖动等随机属性
+    pub fn create_bullet(&mut self, position: Vec2, velocity: Vec2, damage: i32, is_player_bullet: bool, bullet_type: BulletType) -> Bullet {
+        let mut bullet = Bullet::new(position, velocity, damage, is_player_bullet, bullet_type);
+        bullet.rng = Xoroshiro32PlusPlus::new(self.seeder.next_seed());
+        bullet
+    }
+
+    /// 把已经通过`create_bullet`拿到专属种子的子弹放入暂存区，下次`tick`时并入正式列表
+    pub fn push_bullet(&mut self, bullet: Bullet) {
+        self.new_bullets.push(bullet);
+    }
+
+    /// 批量版`push_bullet`，用于敌人一轮攻击一次性产出多颗子弹的场景；这些子弹是
+    /// `BulletEmitter::emit`绕过`create_bullet`直接拼出来的，入队前在这里统一补发种子，
+    /// 否则它们会一直带着`Bullet::new`的占位种子，变成全场唯一不可重放的随机源
+    pub fn push_bullets(&mut self, bullets: Vec<Bullet>) {
+        for mut bullet in bullets {
+            bullet.rng = Xoroshiro32PlusPlus::new(self.seeder.next_seed());
+            self.new_bullets.push(bullet);
+        }
+    }
+
+    /// 统计某种子弹的在场数量，既算已经并入的`bullets`，也算本帧还没合并的`new_bullets`暂存区，
+    /// 否则同一帧内连续调用的武器没法靠这个数字互相看见彼此刚刚造出来的子弹
+    pub fn count_bullets(&self, bullet_type: BulletType) -> usize {
+        self.bullets.iter().chain(self.new_bullets.iter()).filter(|bullet| bullet.bullet_type == bullet_type).count()
+    }
+
+    /// 并入暂存区，推进全部子弹的弹幕脚本/行为/重力/寿命，再检测玩家子弹与敌人的碰撞；
+    /// 碰撞检测会就地更新/移除命中的子弹，但扣血等需要改动敌人列表的部分通过返回值交给调用方
+    pub fn tick(&mut self, dt: f32, enemies: &[Enemy], player_position: Vec2, screen_width: f32, screen_height: f32, items: &mut Vec<Item>) -> BulletHitReport {
+        self.bullets.append(&mut self.new_bullets);
+
+        let enemy_positions: Vec<Vec2> = enemies.iter().map(|enemy| enemy.position).collect();
+        let player_position_slice = [player_position];
+        let mut dissipations = Vec::new();
+
+        for bullet in &mut self.bullets {
+            bullet.tick_pattern_motion();
+            if bullet.is_player_bullet {
+                bullet.tick_behavior(&enemy_positions, dt);
+            } else {
+                bullet.tick_behavior(&player_position_slice, dt);
+            }
+
+            if bullet.gravity != 0.0 {
+                bullet.velocity.y = (bullet.velocity.y + bullet.gravity * dt).min(bullet.max_fall_speed);
+            }
+
+            bullet.position.x += bullet.velocity.x * dt * 100.0;
+            bullet.position.y += bullet.velocity.y * dt * 100.0;
+
+            bullet.life_frames = bullet.life_frames.saturating_sub(1);
+            if bullet.life_frames == 0 {
+                dissipations.push(bullet.position);
+            }
+
+            if bullet.ricochet_count > 0 {
+                let mut bounced = false;
+                if bullet.position.x <= 0.0 || bullet.position.x >= screen_width {
+                    bullet.velocity.x = -bullet.velocity.x;
+                    bullet.ricochet_count -= 1;
+                    bounced = true;
+                }
+                if bullet.position.y <= 0.0 || bullet.position.y >= screen_height {
+                    if bullet.gravity != 0.0 && bullet.position.y >= screen_height {
+                        // 重力弹触地带能量损耗地弹起，而非完全镜面反射
+                        bullet.velocity.y = -bullet.velocity.y * 0.6;
+                    } else {
+                        bullet.velocity.y = -bullet.velocity.y;
+                    }
+                    bullet.ricochet_count -= 1;
+                    bounced = true;
+                }
+                if bounced {
+                    bullet.position.x = bullet.position.x.clamp(0.0, screen_width);
+                    bullet.position.y = bullet.position.y.clamp(0.0, screen_height);
+                    bullet.hit_enemies.clear();
+                }
+            }
+        }
+
+        for position in dissipations {
+            items.push(Item::new_dissipation(position));
+        }
+
+        self.bullets.retain(|bullet| {
+            if bullet.life_frames == 0 {
+                false
+            } else if bullet.ricochet_count > 0 {
+                true
+            } else {
+                bullet.position.y > -50.0 && bullet.position.y < screen_height + 50.0 &&
+                bullet.position.x > -50.0 && bullet.position.x < screen_width + 50.0
+            }
+        });
+
+        self.collide_with_enemies(enemies)
+    }
+
+    /// 玩家子弹一侧的碰撞检测：就地更新命中列表/穿透计数，移除耗尽穿透的子弹，
+    /// 并把每一次命中对应的伤害/燃烧/爆炸信息汇总成`BulletHitReport`返回
+    fn collide_with_enemies(&mut self, enemies: &[Enemy]) -> BulletHitReport {
+        let mut report = BulletHitReport { enemy_hits: Vec::new(), enemy_burning_applies: Vec::new(), explosion_damages: Vec::new() };
+        let mut bullets_to_remove = Vec::new();
+        let mut bullet_piercing_updates = Vec::new();
+        let mut bullet_hit_updates = Vec::new();
+
+        for (bullet_idx, bullet) in self.bullets.iter().enumerate() {
+            if !bullet.is_player_bullet {
+                continue;
+            }
+
+            let mut should_remove_bullet = false;
+            let mut new_hit_enemies = bullet.hit_enemies.clone();
+
+            for (enemy_idx, enemy) in enemies.iter().enumerate() {
+                if enemy.health <= 0 || bullet.hit_enemies.contains(&enemy_idx) {
+                    continue;
+                }
+
+                let distance = bullet.position.distance(&enemy.position);
+                if distance < 30.0 {
+                    new_hit_enemies.push(enemy_idx);
+
+                    let damage = bullet.damage;
+                    if bullet.burning_damage > 0 {
+                        report.enemy_burning_applies.push((enemy_idx, StatusEffect::new(
+                            STATUS_ID_BURNING,
+                            3.0,
+                            1.0,
+                            EffectKind::Burning(bullet.burning_damage),
+                        )));
+                    }
+
+                    report.enemy_hits.push((enemy_idx, damage, bullet.element, bullet.is_crit));
+
+                    if bullet.explosion_damage > 0.0 {
+                        let explosion_dmg = (damage as f32 * bullet.explosion_damage) as i32;
+                        report.explosion_damages.push((enemy.position, explosion_dmg, bullet.element));
+                    }
+
+                    if bullet.piercing_count != 9999 && bullet.piercing_count > 0 {
+                        bullet_piercing_updates.push((bullet_idx, bullet.piercing_count - 1));
+                        if bullet.piercing_count - 1 <= 0 {
+                            should_remove_bullet = true;
+                        }
+                    } else if bullet.piercing_count == 0 {
+                        should_remove_bullet = true;
+                    }
+
+                    if bullet.piercing_count == 0 {
+                        break;
+                    }
+                }
+            }
+
+            if new_hit_enemies.len() > bullet.hit_enemies.len() {
+                bullet_hit_updates.push((bullet_idx, new_hit_enemies));
+            }
+
+            if should_remove_bullet {
+                bullets_to_remove.push(bullet_idx);
+            }
+        }
+
+        for (bullet_idx, new_hit_list) in bullet_hit_updates {
+            if let Some(bullet) = self.bullets.get_mut(bullet_idx) {
+                bullet.hit_enemies = new_hit_list;
+            }
+        }
+
+        for (bullet_idx, new_piercing) in bullet_piercing_updates {
+            if let Some(bullet) = self.bullets.get_mut(bullet_idx) {
+                bullet.piercing_count = new_piercing;
+            }
+        }
+
+        bullets_to_remove.sort_unstable();
+        bullets_to_remove.reverse();
+        for idx in bullets_to_remove {
+            if idx < self.bullets.len() {
+                self.bullets.remove(idx);
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fire_action(mode: ValueMode, value: f32) -> PatternAction {
+        PatternAction::Fire {
+            dir: DirSpec { mode, value },
+            speed: SpeedSpec { mode: ValueMode::Absolute, value: 1.0 },
+            bullet_type: BulletType::EnemyGeneric,
+        }
+    }
+
+    #[test]
+    fn fire_emits_immediately_and_wait_holds_for_the_given_frames() {
+        let pattern = vec![
+            fire_action(ValueMode::Absolute, 90.0),
+            PatternAction::Wait(2),
+            fire_action(ValueMode::Absolute, 90.0),
+        ];
+        let mut runner = PatternRunner::new();
+        let origin = Vec2::new(0.0, 0.0);
+        let player_pos = Vec2::new(0.0, 100.0);
+        let mut emitted = Vec::new();
+
+        runner.step(&pattern, origin, player_pos, 5, &mut emitted);
+        assert_eq!(emitted.len(), 1);
+
+        runner.step(&pattern, origin, player_pos, 5, &mut emitted);
+        assert_eq!(emitted.len(), 1, "仍在Wait倒计时中，不应该再发射");
+
+        runner.step(&pattern, origin, player_pos, 5, &mut emitted);
+        assert_eq!(emitted.len(), 1, "Wait(2)意味着还要再等一帧");
+
+        runner.step(&pattern, origin, player_pos, 5, &mut emitted);
+        assert_eq!(emitted.len(), 2, "倒计时归零后应该执行到下一个Fire");
+    }
+
+    #[test]
+    fn repeat_fires_body_the_requested_number_of_times() {
+        let pattern = vec![PatternAction::Repeat {
+            times: 3,
+            body: vec![fire_action(ValueMode::Absolute, 90.0)],
+        }];
+        let mut runner = PatternRunner::new();
+        let origin = Vec2::new(0.0, 0.0);
+        let player_pos = Vec2::new(0.0, 100.0);
+        let mut emitted = Vec::new();
+
+        let signal = runner.step(&pattern, origin, player_pos, 5, &mut emitted);
+
+        assert_eq!(emitted.len(), 3);
+        assert!(!signal.vanish);
+    }
+
+    #[test]
+    fn vanish_action_sets_the_vanish_signal() {
+        let pattern = vec![PatternAction::Vanish];
+        let mut runner = PatternRunner::new();
+        let mut emitted = Vec::new();
+
+        let signal = runner.step(&pattern, Vec2::new(0.0, 0.0), Vec2::new(0.0, 0.0), 5, &mut emitted);
+
+        assert!(signal.vanish);
+    }
+}
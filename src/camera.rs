@@ -0,0 +1,58 @@
+use macroquad::prelude::*;
+
+// ==================== 滚动摄像机 ====================
+
+/// 跟随玩家并裁剪到地图边界内的2D摄像机
+pub struct GameCamera {
+    pub target: macroquad::math::Vec2,
+    pub viewport_w: f32,
+    pub viewport_h: f32,
+    pub map_w: f32,
+    pub map_h: f32,
+}
+
+impl GameCamera {
+    pub fn new(viewport_w: f32, viewport_h: f32, map_w: f32, map_h: f32) -> Self {
+        Self { target: macroquad::math::Vec2::new(0.0, 0.0), viewport_w, viewport_h, map_w, map_h }
+    }
+
+    /// 让摄像机中心对准玩家的像素坐标，并夹紧到地图范围内
+    pub fn follow(&mut self, player_pixel_pos: macroquad::math::Vec2) {
+        let half_w = self.viewport_w / 2.0;
+        let half_h = self.viewport_h / 2.0;
+
+        let min_x = half_w.min(self.map_w - half_w).max(0.0);
+        let max_x = (self.map_w - half_w).max(half_w);
+        let min_y = half_h.min(self.map_h - half_h).max(0.0);
+        let max_y = (self.map_h - half_h).max(half_h);
+
+        self.target.x = player_pixel_pos.x.clamp(min_x.min(max_x), max_x.max(min_x));
+        self.target.y = player_pixel_pos.y.clamp(min_y.min(max_y), max_y.max(min_y));
+    }
+
+    fn to_macroquad_camera(&self) -> Camera2D {
+        Camera2D {
+            target: self.target,
+            zoom: macroquad::math::Vec2::new(2.0 / self.viewport_w, 2.0 / self.viewport_h),
+            ..Default::default()
+        }
+    }
+
+    /// 世界绘制前调用：切到跟随玩家的摄像机
+    pub fn activate(&self) {
+        set_camera(&self.to_macroquad_camera());
+    }
+
+    /// HUD绘制前调用：恢复固定的屏幕空间摄像机
+    pub fn deactivate(&self) {
+        set_default_camera();
+    }
+
+    pub fn world_to_screen(&self, world_pos: macroquad::math::Vec2) -> macroquad::math::Vec2 {
+        self.to_macroquad_camera().world_to_screen(world_pos)
+    }
+
+    pub fn screen_to_world(&self, screen_pos: macroquad::math::Vec2) -> macroquad::math::Vec2 {
+        self.to_macroquad_camera().screen_to_world(screen_pos)
+    }
+}
@@ -0,0 +1,83 @@
+use crate::*;
+use ::rand::prelude::*;
+
+// ==================== 编队系统 ====================
+
+/// 单个编队最多容纳的成员数，出生时按这个上限裁剪，避免椭圆轨道上挤得太密
+pub const FORMATION_MEMBER_MAX: usize = 8;
+
+/// 编队队形模板
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormationTemplate {
+    VWedge,
+    HorizontalLine,
+    EllipseOrbit,
+}
+
+/// 一组以固定几何队形整体移动的敌人
+#[derive(Debug, Clone)]
+pub struct Formation {
+    pub template: FormationTemplate,
+    pub pivot: Vec2,
+    pub radius: Vec2,
+    pub speed: f32,
+    pub angle: f32,
+    pub members: Vec<usize>,
+}
+
+impl Formation {
+    /// 计算第`slot_index`个成员（共`member_count`个）相对于`pivot`的目标偏移
+    pub fn slot_offset(&self, slot_index: usize, member_count: usize) -> Vec2 {
+        match self.template {
+            FormationTemplate::VWedge => {
+                let spacing = 30.0;
+                let row = (slot_index + 1) / 2;
+                let side = if slot_index % 2 == 0 { 1.0 } else { -1.0 };
+                Vec2::new(side * row as f32 * spacing, row as f32 * spacing * 0.6)
+            }
+            FormationTemplate::HorizontalLine => {
+                let spacing = 40.0;
+                let center_offset = (member_count as f32 - 1.0) / 2.0;
+                Vec2::new((slot_index as f32 - center_offset) * spacing, 0.0)
+            }
+            FormationTemplate::EllipseOrbit => {
+                let step = std::f32::consts::TAU / member_count.max(1) as f32;
+                let slot_angle = self.angle + step * slot_index as f32;
+                Vec2::new(slot_angle.cos() * self.radius.x, slot_angle.sin() * self.radius.y)
+            }
+        }
+    }
+
+    /// 第`slot_index`个成员在世界坐标中的目标位置
+    pub fn slot_target(&self, slot_index: usize, member_count: usize) -> Vec2 {
+        let offset = self.slot_offset(slot_index, member_count);
+        Vec2::new(self.pivot.x + offset.x, self.pivot.y + offset.y)
+    }
+}
+
+/// 按模板随机生成一个编队，选择随机的pivot/radius
+pub struct FormationMaker;
+
+impl FormationMaker {
+    pub fn make_random(rng: &mut ThreadRng, screen_width: f32) -> Formation {
+        let templates = [
+            FormationTemplate::VWedge,
+            FormationTemplate::HorizontalLine,
+            FormationTemplate::EllipseOrbit,
+        ];
+        let template = templates[rng.gen_range(0..templates.len())];
+
+        let margin = screen_width * 0.25;
+        let pivot = Vec2::new(rng.gen_range(margin..screen_width - margin), 120.0);
+        let radius = Vec2::new(rng.gen_range(60.0..120.0), rng.gen_range(30.0..60.0));
+
+        Formation {
+            template,
+            pivot,
+            radius,
+            speed: rng.gen_range(0.5..1.2),
+            angle: 0.0,
+            members: Vec::new(),
+        }
+    }
+}
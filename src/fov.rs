@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+
+// ==================== 视野与战争迷雾 ====================
+
+/// 单个格子的可见性状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileVisibility {
+    /// 从未被看到过
+    Unseen,
+    /// 之前看到过，但当前不在视野内
+    Explored,
+    /// 当前处于视野内
+    Visible,
+}
+
+/// 记录地图上每个格子的可见性状态
+pub struct FovMap {
+    pub width: i32,
+    pub height: i32,
+    states: Vec<TileVisibility>,
+}
+
+impl FovMap {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            states: vec![TileVisibility::Unseen; (width * height).max(0) as usize],
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some((y * self.width + x) as usize)
+        }
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> TileVisibility {
+        self.index(x, y).map(|i| self.states[i]).unwrap_or(TileVisibility::Unseen)
+    }
+
+    /// 将上一帧的Visible降级为Explored，再根据本帧的视野集合重新标记
+    pub fn update(&mut self, visible: &HashSet<(i32, i32)>) {
+        for state in &mut self.states {
+            if *state == TileVisibility::Visible {
+                *state = TileVisibility::Explored;
+            }
+        }
+        for &(x, y) in visible {
+            if let Some(i) = self.index(x, y) {
+                self.states[i] = TileVisibility::Visible;
+            }
+        }
+    }
+}
+
+/// 8个象限的坐标变换系数 (xx, xy, yx, yy)
+const OCTANTS: [(i32, i32, i32, i32); 8] = [
+    (1, 0, 0, 1),
+    (0, 1, 1, 0),
+    (0, -1, 1, 0),
+    (-1, 0, 0, 1),
+    (-1, 0, 0, -1),
+    (0, -1, -1, 0),
+    (0, 1, -1, 0),
+    (1, 0, 0, -1),
+];
+
+/// 对称递归阴影投射，返回从`origin`可见的格子集合（含自身）
+pub fn compute_fov(
+    origin: (i32, i32),
+    radius: i32,
+    is_opaque: impl Fn(i32, i32) -> bool,
+) -> HashSet<(i32, i32)> {
+    let mut visible = HashSet::new();
+    visible.insert(origin);
+
+    for &(xx, xy, yx, yy) in &OCTANTS {
+        cast_octant(origin, radius, &is_opaque, &mut visible, 1, 1.0, 0.0, xx, xy, yx, yy);
+    }
+
+    visible
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cast_octant(
+    origin: (i32, i32),
+    radius: i32,
+    is_opaque: &impl Fn(i32, i32) -> bool,
+    visible: &mut HashSet<(i32, i32)>,
+    row: i32,
+    start_slope: f32,
+    end_slope: f32,
+    xx: i32,
+    xy: i32,
+    yx: i32,
+    yy: i32,
+) {
+    if start_slope < end_slope {
+        return;
+    }
+
+    let mut start_slope = start_slope;
+    let radius_sq = radius * radius;
+
+    for j in row..=radius {
+        let dy = -j;
+        let mut blocked = false;
+        let mut new_start_slope = start_slope;
+
+        for dx in -j..=0 {
+            let (map_x, map_y) = (origin.0 + dx * xx + dy * xy, origin.1 + dx * yx + dy * yy);
+
+            let left_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+            let right_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+
+            if start_slope < right_slope {
+                continue;
+            }
+            if end_slope > left_slope {
+                break;
+            }
+
+            if dx * dx + dy * dy <= radius_sq {
+                visible.insert((map_x, map_y));
+            }
+
+            let opaque = is_opaque(map_x, map_y);
+            if blocked {
+                if opaque {
+                    new_start_slope = right_slope;
+                    continue;
+                } else {
+                    blocked = false;
+                    start_slope = new_start_slope;
+                }
+            } else if opaque && j < radius {
+                blocked = true;
+                cast_octant(origin, radius, is_opaque, visible, j + 1, start_slope, left_slope, xx, xy, yx, yy);
+                new_start_slope = right_slope;
+            }
+        }
+
+        if blocked {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_field_sees_full_radius() {
+        let visible = compute_fov((5, 5), 3, |_, _| false);
+        assert!(visible.contains(&(5, 5)));
+        assert!(visible.contains(&(5, 8)));
+        assert!(!visible.contains(&(5, 9)));
+    }
+
+    #[test]
+    fn wall_casts_a_shadow() {
+        let is_opaque = |x: i32, y: i32| (x, y) == (5, 4);
+        let visible = compute_fov((5, 5), 5, is_opaque);
+        assert!(visible.contains(&(5, 4)));
+        assert!(!visible.contains(&(5, 3)));
+        assert!(!visible.contains(&(5, 1)));
+    }
+}
@@ -1,8 +1,20 @@
 use std::time::Instant;
 use macroquad::prelude::*;
+use macroquad::audio::{Sound, PlaySoundParams};
 use ::rand::prelude::*;
-use mysql::*;
-use mysql::prelude::*;
+
+mod bullets;
+pub use bullets::*;
+mod formation;
+pub use formation::*;
+mod status_effects;
+pub use status_effects::*;
+mod shop;
+pub use shop::*;
+mod persistence;
+pub use persistence::*;
+mod audio;
+pub use audio::*;
 
 // ==================== 基础类型定义 ====================
 
@@ -25,13 +37,23 @@ impl Vec2 {
     }
     
     pub fn normalize(&self) -> Vec2 {
-        let length = (self.x.powi(2) + self.y.powi(2)).sqrt();
+        let length = self.length();
         if length > 0.0 {
             Vec2::new(self.x / length, self.y / length)
         } else {
             Vec2::new(0.0, 0.0)
         }
     }
+
+    pub fn length(&self) -> f32 {
+        (self.x.powi(2) + self.y.powi(2)).sqrt()
+    }
+
+    /// 按弧度`angle`旋转该向量，用于给子弹速度叠加后坐力/散布偏转
+    pub fn rotated(&self, angle: f32) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
 }
 
 // ==================== 游戏状态枚举 ====================
@@ -43,8 +65,12 @@ pub enum GameState {
     WeaponSelect,
     Login,
     Battle,
+    Paused,
     RogueSelection,
+    StatAllocation,
     GameOver,
+    Leaderboard,
+    Shop,
 }
 
 /// 输入模式
@@ -65,6 +91,27 @@ pub enum WeaponType {
     Shotgun,
 }
 
+/// 武器等级，到Level3后不再继续升级
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeaponLevel {
+    Level1,
+    Level2,
+    Level3,
+}
+
+/// 后坐力每秒衰减量，单位同`Player::recoil`（1/4度）
+const RECOIL_DECAY_PER_SEC: f32 = 20.0;
+/// 连续这么久没挨打，SP回复速度翻倍
+const NO_HIT_SP_REGEN_BONUS_DELAY: f32 = 3.0;
+
+/// `Weapon::add_experience`的返回结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AddExperienceResult {
+    None,
+    LevelUp,
+    MaxLevel,
+}
+
 /// 武器结构
 #[derive(Debug, Clone)]
 pub struct Weapon {
@@ -73,7 +120,9 @@ pub struct Weapon {
     pub attack_speed: f32,
     pub bullet_speed: f32,
     pub bullet_count: i32,
-    pub enhancement_level: i32,
+    pub level: WeaponLevel,
+    pub experience: u16,
+    pub max_experience: u16,
 }
 
 impl Weapon {
@@ -83,22 +132,108 @@ impl Weapon {
             WeaponType::Laser => (4, 1.25, 0.0, 1),
             WeaponType::Shotgun => (4, 1.0, 3.0, 3),
         };
-        
+
         Self {
             weapon_type,
             attack_power,
             attack_speed,
             bullet_speed,
             bullet_count,
-            enhancement_level: 0,
+            level: WeaponLevel::Level1,
+            experience: 0,
+            max_experience: 100,
         }
     }
-    
+
     pub fn get_total_attack_power(&self) -> i32 {
-        self.attack_power + self.enhancement_level
+        self.attack_power
+    }
+
+    /// 每次升级各武器类型的成长幅度：(攻击力, 子弹数, 攻速)
+    fn level_up_gains(weapon_type: &WeaponType) -> (i32, i32, f32) {
+        match weapon_type {
+            WeaponType::MachineGun => (1, 1, 0.1),
+            WeaponType::Laser => (2, 0, 0.05),
+            WeaponType::Shotgun => (1, 1, 0.05),
+        }
+    }
+
+    /// 散射参数：(基础散布, 后坐力系数, 单次开火后坐力增量)，单位为1/4度
+    fn spread_params(weapon_type: &WeaponType) -> (f32, f32, f32) {
+        match weapon_type {
+            WeaponType::Laser => (2.0, 0.5, 1.0),
+            WeaponType::MachineGun => (6.0, 1.0, 3.0),
+            WeaponType::Shotgun => (16.0, 1.5, 5.0),
+        }
+    }
+
+    /// 为武器累积经验，溢出的经验会结转到下一级；已满级时直接返回`MaxLevel`
+    pub fn add_experience(&mut self, exp: u16) -> AddExperienceResult {
+        if self.level == WeaponLevel::Level3 {
+            return AddExperienceResult::MaxLevel;
+        }
+
+        self.experience += exp;
+
+        if self.experience < self.max_experience {
+            return AddExperienceResult::None;
+        }
+
+        let overflow = self.experience - self.max_experience;
+        let (atk_gain, bullet_gain, speed_gain) = Self::level_up_gains(&self.weapon_type);
+        self.attack_power += atk_gain;
+        self.bullet_count += bullet_gain;
+        self.attack_speed += speed_gain;
+
+        self.level = match self.level {
+            WeaponLevel::Level1 => WeaponLevel::Level2,
+            WeaponLevel::Level2 => WeaponLevel::Level3,
+            WeaponLevel::Level3 => WeaponLevel::Level3,
+        };
+        self.max_experience = self.max_experience.saturating_add(50);
+        self.experience = overflow;
+
+        if self.level == WeaponLevel::Level3 {
+            self.experience = 0;
+        }
+
+        AddExperienceResult::LevelUp
     }
 }
 
+// ==================== 属性克制系统 ====================
+
+/// 攻击/防御元素属性
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Element {
+    Neutral,
+    Fire,
+    Ice,
+    Electric,
+    Explosive,
+}
+
+const ELEMENT_COUNT: usize = 5;
+
+/// 属性克制表：`ATTR_FIX[攻击属性][防御属性]`为伤害倍率，方便在一处统一调整数值平衡
+const ATTR_FIX: [[f32; ELEMENT_COUNT]; ELEMENT_COUNT] = {
+    // 行: Neutral, Fire, Ice, Electric, Explosive (攻击方)
+    // 列: Neutral, Fire, Ice, Electric, Explosive (防御方)
+    let mut table = [[1.0; ELEMENT_COUNT]; ELEMENT_COUNT];
+    table[Element::Fire as usize][Element::Fire as usize] = 0.5;
+    table[Element::Fire as usize][Element::Ice as usize] = 1.5;
+    table[Element::Ice as usize][Element::Fire as usize] = 0.5;
+    // 重甲舰的防御属性为Neutral（厚重金属装甲），对电属性格外敏感
+    table[Element::Electric as usize][Element::Neutral as usize] = 1.25;
+    table[Element::Explosive as usize][Element::Explosive as usize] = 0.75;
+    table
+};
+
+/// 按攻击属性和目标防御属性查出克制倍率
+pub fn attr_fix(attack: Element, defense: Element) -> f32 {
+    ATTR_FIX[attack as usize][defense as usize]
+}
+
 // ==================== 敌人系统 ====================
 
 /// 敌人类型
@@ -129,24 +264,45 @@ pub struct Enemy {
     pub movement_timer: f32,
     pub target_position: Vec2,
     pub has_reached_zone: bool,
+    pub formation_slot: Option<usize>,
+    pub status_effects: Vec<StatusEffect>,
+    pub defense_element: Element,
+    pub phase1_runner: PatternRunner,
+    pub phase2_runner: PatternRunner,
+    /// 由当前状态效果算出的移动/开火速度倍率，`tick_status_effects`每帧重算
+    pub status_speed_multiplier: f32,
+    /// 当前是否处于眩晕，`tick_status_effects`每帧重算
+    pub is_stunned: bool,
+    /// 距离下一次可以被冰冻还剩多久，防止一梭子弹反复触发冰冻的持续时间刷新
+    pub freeze_proc_cooldown: f32,
+    /// 距离下一次可以被眩晕还剩多久，同上
+    pub stun_proc_cooldown: f32,
 }
 
 impl Enemy {
     pub fn new(enemy_type: EnemyType, position: Vec2) -> Self {
         let mut rng = ::rand::thread_rng();
-        
+
         let (health, velocity, bullet_damage, collision_damage, movement_pattern, target_position) = match enemy_type {
             EnemyType::Scout => (20, Vec2::new(0.0, 0.5), 0, 5, 0, Vec2::new(0.0, 0.0)),
             EnemyType::Heavy => (30, Vec2::new(0.0, 0.8), 2, 5, rng.gen_range(1..=4), Vec2::new(position.x, 120.0)),
             EnemyType::Carrier => (100, Vec2::new(0.0, 0.3), 0, 10, 0, Vec2::new(0.0, 0.0)),
             EnemyType::Boss => (150, Vec2::new(0.0, 0.5), 10, 20, 1, Vec2::new(position.x, 100.0)),
         };
-        
+
         let special_state = match enemy_type {
             EnemyType::Boss => 1,
             _ => 0,
         };
-        
+
+        // 不同敌人类型的装甲对元素攻击的反应不同
+        let defense_element = match enemy_type {
+            EnemyType::Scout => Element::Neutral,
+            EnemyType::Heavy => Element::Neutral,
+            EnemyType::Carrier => Element::Ice,
+            EnemyType::Boss => Element::Explosive,
+        };
+
         Self {
             enemy_type,
             position,
@@ -164,9 +320,59 @@ impl Enemy {
             movement_timer: 0.0,
             target_position,
             has_reached_zone: false,
+            formation_slot: None,
+            status_effects: Vec::new(),
+            defense_element,
+            phase1_runner: PatternRunner::new(),
+            phase2_runner: PatternRunner::new(),
+            status_speed_multiplier: 1.0,
+            is_stunned: false,
+            freeze_proc_cooldown: 0.0,
+            stun_proc_cooldown: 0.0,
         }
     }
-    
+
+    pub fn apply_effect(&mut self, effect: StatusEffect) {
+        apply_status_effect(&mut self.status_effects, effect);
+    }
+
+    /// 推进所有状态效果：燃烧按`tick_interval`跳动扣血，冰冻/眩晕重算本帧的移速倍率与是否眩晕，
+    /// 到期的效果被移除；同时把冰冻/眩晕的触发冷却也一起倒数，避免一梭子弹反复刷新持续时间
+    pub fn tick_status_effects(&mut self, dt: f32) {
+        let mut burn_damage = 0;
+        let mut speed_multiplier = 1.0;
+        let mut stunned = false;
+
+        for effect in &mut self.status_effects {
+            effect.remaining -= dt;
+            effect.time_since_tick += dt;
+
+            if effect.time_since_tick >= effect.tick_interval {
+                effect.time_since_tick -= effect.tick_interval;
+                if let EffectKind::Burning(per_stack) = effect.kind {
+                    burn_damage += per_stack * effect.stacks as i32;
+                }
+            }
+
+            match effect.kind {
+                EffectKind::Slow(factor) => speed_multiplier *= factor,
+                EffectKind::Stun => stunned = true,
+                _ => {}
+            }
+        }
+
+        if burn_damage > 0 {
+            self.take_damage(burn_damage, Element::Fire);
+        }
+
+        self.status_effects.retain(|e| e.remaining > 0.0);
+        self.status_speed_multiplier = speed_multiplier;
+        self.is_stunned = stunned;
+
+        self.freeze_proc_cooldown = (self.freeze_proc_cooldown - dt).max(0.0);
+        self.stun_proc_cooldown = (self.stun_proc_cooldown - dt).max(0.0);
+    }
+
     pub fn get_drop_gold(&self) -> i32 {
         match self.enemy_type {
             EnemyType::Scout => 10,
@@ -185,69 +391,28 @@ impl Enemy {
         }
     }
     
-    pub fn take_damage(&mut self, damage: i32) {
+    /// 返回实际扣除的血量（经过属性克制加成/减免），调用方需要据此展示伤害数字时应该用这个返回值，
+    /// 而不是传入的原始`damage`，否则克制/抵抗关系会让飘字和实际掉血对不上
+    pub fn take_damage(&mut self, damage: i32, attack_element: Element) -> i32 {
         if self.is_invincible {
-            return;
+            return 0;
         }
-        
+
+        let damage = (damage as f32 * attr_fix(attack_element, self.defense_element)) as i32;
+
         if self.shield_health > 0 {
             self.shield_health = (self.shield_health - damage).max(0);
         } else {
             self.health = (self.health - damage).max(0);
         }
-        
+
         // Boss进入第二阶段
         if self.enemy_type == EnemyType::Boss && self.health <= 75 && self.special_state == 1 {
             self.special_state = 2;
             self.is_invincible = true;
         }
-    }
-}
-
-// ==================== 子弹系统 ====================
-
-/// 子弹类型
-#[derive(Debug, Clone, PartialEq)]
-pub enum BulletType {
-    PlayerMachineGun,
-    PlayerLaser,
-    PlayerShotgun,
-    EnemyHeavy,
-    EnemyBoss,
-    EnemyGeneric,
-}
-
-/// 子弹结构
-#[derive(Debug, Clone)]
-pub struct Bullet {
-    pub position: Vec2,
-    pub velocity: Vec2,
-    pub damage: i32,
-    pub is_player_bullet: bool,
-    pub piercing_count: i32,
-    pub ricochet_count: i32,
-    pub burning_damage: i32,
-    pub explosion_damage: f32,
-    pub is_crit: bool,
-    pub hit_enemies: Vec<usize>,
-    pub bullet_type: BulletType,
-}
 
-impl Bullet {
-    pub fn new(position: Vec2, velocity: Vec2, damage: i32, is_player_bullet: bool, bullet_type: BulletType) -> Self {
-        Self {
-            position,
-            velocity,
-            damage,
-            is_player_bullet,
-            piercing_count: 0,
-            ricochet_count: 0,
-            burning_damage: 0,
-            explosion_damage: 0.0,
-            is_crit: false,
-            hit_enemies: Vec::new(),
-            bullet_type,
-        }
+        damage
     }
 }
 
@@ -257,6 +422,8 @@ impl Bullet {
 #[derive(Debug, Clone, PartialEq)]
 pub enum ItemType {
     HealthPack,
+    /// 子弹到期消失时播放的短暂消散标记，纯视觉效果，不可拾取
+    Dissipation,
 }
 
 /// 道具结构
@@ -279,6 +446,70 @@ impl Item {
             spawn_time: Instant::now(),
         }
     }
+
+    /// 子弹到期消失处生成一个静止、短命的消散标记
+    pub fn new_dissipation(position: Vec2) -> Self {
+        Self {
+            item_type: ItemType::Dissipation,
+            position,
+            velocity: Vec2::new(0.0, 0.0),
+            value: 0,
+            spawn_time: Instant::now(),
+        }
+    }
+}
+
+// ==================== 浮空文字系统 ====================
+
+/// 浮空提示文字存活的总时长（秒），到期后在`update_floating_texts`里被剔除
+pub const FLOATING_TEXT_LIFETIME: f32 = 0.8;
+
+/// 命中/治疗/金币等瞬时反馈文字，只做纯展示用，不参与任何战斗计算
+#[derive(Debug, Clone)]
+pub struct FloatingText {
+    pub text: String,
+    pub position: Vec2,
+    pub color: Color,
+    pub spawn_time: Instant,
+    pub velocity: Vec2,
+    pub font_size: f32,
+}
+
+impl FloatingText {
+    /// 普通命中/治疗/金币提示：默认每秒上漂40像素，与道具每秒50像素的下坠速度相近
+    pub fn new(text: String, position: Vec2, color: Color) -> Self {
+        Self {
+            text,
+            position,
+            color,
+            spawn_time: Instant::now(),
+            velocity: Vec2::new(0.0, -40.0),
+            font_size: 16.0,
+        }
+    }
+
+    /// Boss命中或暴击用更大字号，在一堆白字里更抓眼
+    pub fn new_emphasized(text: String, position: Vec2, color: Color) -> Self {
+        let mut floating_text = Self::new(text, position, color);
+        floating_text.font_size = 24.0;
+        floating_text
+    }
+}
+
+/// 伤害命中飘字的统一生成入口。各命中结算点都在持有`self.enemies`某种借用的情况下调用，
+/// 写成只接`&mut Vec<FloatingText>`的自由函数而不是`Game`的方法，避免和那个借用冲突
+fn push_damage_floating_text(list: &mut Vec<FloatingText>, damage: i32, position: Vec2, emphasize: bool) {
+    let text = format!("-{}", damage);
+    if emphasize {
+        list.push(FloatingText::new_emphasized(text, position, ORANGE));
+    } else {
+        list.push(FloatingText::new(text, position, WHITE));
+    }
+}
+
+/// 击杀掉落金币飘字的统一生成入口，同样因为借用原因写成自由函数
+fn push_coin_floating_text(list: &mut Vec<FloatingText>, coins: i32, position: Vec2) {
+    list.push(FloatingText::new(format!("+{} coins", coins), position, YELLOW));
 }
 
 // ==================== 肉鸽升级系统 ====================
@@ -329,6 +560,40 @@ impl RogueUpgrade {
     }
 }
 
+// ==================== 主动技能系统 ====================
+
+/// 主动技能的具体种类，每种技能自带效果所需的数值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SkillKind {
+    /// 以玩家为圆心清场：清除范围内的敌方子弹，对范围内敌人造成一次性伤害
+    NovaBurst { radius: f32, damage: i32 },
+    /// `duration`秒内攻速乘以`multiplier`
+    OverdriveFire { multiplier: f32, duration: f32 },
+}
+
+/// 主动技能：靠按键手动触发，消耗SP并进入冷却
+#[derive(Debug, Clone)]
+pub struct ActiveSkill {
+    pub cooldown: f32,
+    pub cooldown_timer: f32,
+    pub sp_cost: f32,
+    pub kind: SkillKind,
+}
+
+impl ActiveSkill {
+    pub fn new(cooldown: f32, sp_cost: f32, kind: SkillKind) -> Self {
+        Self { cooldown, cooldown_timer: 0.0, sp_cost, kind }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.cooldown_timer <= 0.0
+    }
+
+    pub fn tick(&mut self, dt: f32) {
+        self.cooldown_timer = (self.cooldown_timer - dt).max(0.0);
+    }
+}
+
 // ==================== 玩家系统 ====================
 
 /// 玩家结构
@@ -340,7 +605,8 @@ pub struct Player {
     pub level: i32,
     pub experience: i32,
     pub experience_needed: i32,
-    pub weapon: Weapon,
+    pub weapons: Vec<Weapon>,
+    pub current_weapon: usize,
     pub last_shot_time: Instant,
     pub attack_power_bonus: i32,
     pub crit_rate: f32,
@@ -352,9 +618,26 @@ pub struct Player {
     pub explosion_damage: f32,
     pub damage_reduction: i32,
     pub bullet_speed_bonus: f32,
+    pub recoil: f32,
+    pub spread_coefficient: f32,
     pub rogue_upgrades: Vec<RogueUpgrade>,
     pub last_damage_time: Instant,
     pub invincibility_duration: f32,
+    pub status_effects: Vec<StatusEffect>,
+    pub bullet_element: Element,
+    pub homing_shots: bool,
+    /// 命中时冰冻目标1.5秒的概率
+    pub freeze_on_hit_chance: f32,
+    /// 命中Boss时额外触发短暂眩晕的概率
+    pub boss_stun_on_hit_chance: f32,
+    pub sp: f32,
+    pub max_sp: f32,
+    pub sp_regen: f32,
+    pub skill: Option<ActiveSkill>,
+    /// 商店里的移速永久强化，叠加到基础移动速度上
+    pub move_speed_bonus: f32,
+    /// 手动升级模式下各职业路线的投入进度
+    pub stat_allocation: StatAllocation,
 }
 
 impl Player {
@@ -366,7 +649,8 @@ impl Player {
             level: 1,
             experience: 0,
             experience_needed: 100,
-            weapon: Weapon::new(WeaponType::MachineGun),
+            weapons: vec![Weapon::new(WeaponType::MachineGun)],
+            current_weapon: 0,
             last_shot_time: Instant::now(),
             attack_power_bonus: 0,
             crit_rate: 0.0,
@@ -378,12 +662,69 @@ impl Player {
             explosion_damage: 0.0,
             damage_reduction: 0,
             bullet_speed_bonus: 0.0,
+            recoil: 0.0,
+            spread_coefficient: 1.0,
             rogue_upgrades: Vec::new(),
             last_damage_time: Instant::now(),
             invincibility_duration: 0.0,
+            status_effects: Vec::new(),
+            bullet_element: Element::Neutral,
+            homing_shots: false,
+            freeze_on_hit_chance: 0.0,
+            boss_stun_on_hit_chance: 0.0,
+            sp: 0.0,
+            max_sp: 100.0,
+            sp_regen: 8.0,
+            skill: Some(ActiveSkill::new(8.0, 50.0, SkillKind::NovaBurst { radius: 180.0, damage: 40 })),
+            move_speed_bonus: 0.0,
+            stat_allocation: StatAllocation::new(),
         }
     }
-    
+
+    pub fn apply_effect(&mut self, effect: StatusEffect) {
+        apply_status_effect(&mut self.status_effects, effect);
+    }
+
+    /// 推进所有状态效果：燃烧按`tick_interval`跳动扣血，到期的效果被移除
+    pub fn tick_status_effects(&mut self, dt: f32) {
+        let mut burn_damage = 0;
+
+        for effect in &mut self.status_effects {
+            effect.remaining -= dt;
+            effect.time_since_tick += dt;
+
+            if effect.time_since_tick >= effect.tick_interval {
+                effect.time_since_tick -= effect.tick_interval;
+                if let EffectKind::Burning(per_stack) = effect.kind {
+                    burn_damage += per_stack * effect.stacks as i32;
+                }
+            }
+        }
+
+        if burn_damage > 0 {
+            self.take_damage(burn_damage);
+        }
+
+        self.status_effects.retain(|e| e.remaining > 0.0);
+    }
+
+    /// 离上次挨打超过`NO_HIT_SP_REGEN_BONUS_DELAY`秒后，SP回复速度翻倍，鼓励走位苟血而不是贴脸输出
+    pub fn sp_regen_rate(&self) -> f32 {
+        if self.last_damage_time.elapsed().as_secs_f32() >= NO_HIT_SP_REGEN_BONUS_DELAY {
+            self.sp_regen * 2.0
+        } else {
+            self.sp_regen
+        }
+    }
+
+    /// 状态效果里的攻速加成倍率，没有则是1.0；`OverdriveFire`主动技能靠这个生效
+    pub fn attack_speed_multiplier(&self) -> f32 {
+        self.status_effects.iter().filter_map(|e| match e.kind {
+            EffectKind::AttackBoost(mult) => Some(mult),
+            _ => None,
+        }).fold(1.0, f32::max)
+    }
+
     pub fn add_experience(&mut self, exp: i32) {
         self.experience += exp;
     }
@@ -394,16 +735,42 @@ impl Player {
         self.experience_needed = 100 * self.level;
     }
     
+    pub fn weapon(&self) -> &Weapon {
+        &self.weapons[self.current_weapon]
+    }
+
+    pub fn weapon_mut(&mut self) -> &mut Weapon {
+        &mut self.weapons[self.current_weapon]
+    }
+
+    pub fn has_weapon(&self, weapon_type: &WeaponType) -> bool {
+        self.weapons.iter().any(|w| &w.weapon_type == weapon_type)
+    }
+
+    /// 添加一把新武器到库存；已拥有同类型武器时不重复添加
+    pub fn add_weapon(&mut self, weapon_type: WeaponType) {
+        if !self.has_weapon(&weapon_type) {
+            self.weapons.push(Weapon::new(weapon_type));
+        }
+    }
+
+    /// 切换到库存中的下一把武器
+    pub fn next_weapon(&mut self) {
+        if !self.weapons.is_empty() {
+            self.current_weapon = (self.current_weapon + 1) % self.weapons.len();
+        }
+    }
+
     pub fn can_shoot(&self) -> bool {
-        self.last_shot_time.elapsed().as_secs_f32() >= 1.0 / self.weapon.attack_speed
+        self.last_shot_time.elapsed().as_secs_f32() >= 1.0 / (self.weapon().attack_speed * self.attack_speed_multiplier())
     }
-    
+
     pub fn get_total_attack_power(&self) -> i32 {
-        self.weapon.get_total_attack_power() + self.attack_power_bonus
+        self.weapon().get_total_attack_power() + self.attack_power_bonus
     }
-    
+
     pub fn get_total_bullet_count(&self) -> i32 {
-        (self.weapon.bullet_count + self.bullet_count_bonus).min(self.weapon.bullet_count + 5)
+        (self.weapon().bullet_count + self.bullet_count_bonus).min(self.weapon().bullet_count + 5)
     }
     
     pub fn take_damage(&mut self, damage: i32) {
@@ -419,80 +786,6 @@ impl Player {
     }
 }
 
-// ==================== 游戏结算系统 ====================
-
-/// 游戏结算结构
-#[derive(Debug, Clone)]
-pub struct GameResult {
-    pub victory: bool,
-    pub final_level: i32,
-    pub coins_earned: i32,
-    pub experience_gained: i32,
-    pub survival_time: f32,
-    pub enemies_defeated: i32,
-    pub total_damage_dealt: i32,
-    pub weapon_used: WeaponType,
-}
-
-impl GameResult {
-    pub fn new(player: &Player, victory: bool, time: f32, enemies_defeated: i32, total_damage: i32) -> Self {
-        Self {
-            victory,
-            final_level: player.level,
-            coins_earned: 0,
-            experience_gained: 0,
-            survival_time: time,
-            enemies_defeated,
-            total_damage_dealt: total_damage,
-            weapon_used: player.weapon.weapon_type.clone(),
-        }
-    }
-}
-
-// ==================== 用户系统 ====================
-
-/// 用户数据
-#[derive(Debug, Clone)]
-pub struct User {
-    pub username: String,
-    pub password: String,
-    pub is_logged_in: bool,
-}
-
-impl User {
-    pub fn new() -> Self {
-        Self {
-            username: String::new(),
-            password: String::new(),
-            is_logged_in: false,
-        }
-    }
-
-    // 传入 MySQL 连接池和用户输入，验证登录
-    pub fn login(&mut self, pool: &Pool, username: &str, password: &str) -> Result<bool> {
-        let mut conn = pool.get_conn()?;
-
-        // 查询数据库，验证用户名密码是否匹配
-        let result: Option<String> = conn.exec_first(
-            "SELECT password FROM users WHERE username = :username",
-            params! {
-                "username" => username,
-            },
-        )?;
-
-        if let Some(stored_password) = result {
-            if stored_password == password {
-                self.username = username.to_string();
-                self.password = password.to_string();
-                self.is_logged_in = true;
-                return Ok(true);
-            }
-        }
-
-        Ok(false)
-    }
-}
-
 // ==================== 主游戏结构 ====================
 
 /// 游戏主结构
@@ -501,16 +794,23 @@ pub struct Game {
     pub state: GameState,
     pub player: Player,
     pub enemies: Vec<Enemy>,
-    pub bullets: Vec<Bullet>,
+    pub bullet_manager: BulletManager,
     pub items: Vec<Item>,
-    
+    pub formations: Vec<Formation>,
+    pub floating_texts: Vec<FloatingText>,
+
     // 时间相关
     pub start_time: Instant,
     pub last_spawn_time: Instant,
-    
+    /// 本次暂停的起始时刻；非`None`时表示当前处于`GameState::Paused`。
+    /// 恢复时把`start_time`等时间基准前移暂停时长，而不是在每个读取点减一遍，
+    /// 这样所有基于`Instant::elapsed()`的计时（战斗时钟、无敌帧）天然就是冻结的
+    pub pause_started_at: Option<Instant>,
+
     // 游戏数据
     pub coins: i32,
     pub wins: i32,
+    pub shop_upgrades: ShopUpgrades,
     pub available_upgrades: Vec<RogueUpgrade>,
     pub screen_width: f32,
     pub screen_height: f32,
@@ -528,7 +828,10 @@ pub struct Game {
     pub enemies_defeated_this_session: i32,
     pub total_damage_dealt: i32,
     pub game_result: Option<GameResult>,
-    
+
+    // 排行榜
+    pub leaderboard: Vec<LeaderboardEntry>,
+
     // 肉鸽升级相关
     pub rogue_selection_timer: f32,
     pub current_rogue_options: Vec<RogueUpgrade>,
@@ -547,6 +850,16 @@ pub struct Game {
     pub heavy_bullet_texture: Option<Texture2D>,
     pub boss_bullet_texture: Option<Texture2D>,
     pub health_pack_texture: Option<Texture2D>,
+
+    // 音效资源
+    /// 按事件分类的音效池：武器开火、受击/击杀、道具拾取、升级、肉鸽卡牌选择
+    pub audio: AudioManager,
+    pub victory_sound: Option<Sound>,
+    pub death_sound: Option<Sound>,
+    /// 全局音量，`0.0`为静音；在主菜单用按键调整
+    pub master_volume: f32,
+    /// 升级时走哪条路线：`true`为手动分配属性点，`false`为随机肉鸽卡牌；在主菜单用按键切换
+    pub use_stat_allocation: bool,
 }
 
 // ==================== 游戏初始化 ====================
@@ -557,12 +870,16 @@ impl Game {
             state: GameState::MainMenu,
             player: Player::new(),
             enemies: Vec::new(),
-            bullets: Vec::new(),
+            bullet_manager: BulletManager::new(::rand::thread_rng().gen::<u32>()),
             items: Vec::new(),
+            formations: Vec::new(),
+            floating_texts: Vec::new(),
             start_time: Instant::now(),
             last_spawn_time: Instant::now(),
+            pause_started_at: None,
             coins: 0,
             wins: 0,
+            shop_upgrades: ShopUpgrades::new(),
             available_upgrades: Vec::new(),
             screen_width: 800.0,
             screen_height: 600.0,
@@ -576,6 +893,7 @@ impl Game {
             enemies_defeated_this_session: 0,
             total_damage_dealt: 0,
             game_result: None,
+            leaderboard: Vec::new(),
             rogue_selection_timer: 0.0,
             current_rogue_options: Vec::new(),
             rogue_auto_selected: false,
@@ -591,6 +909,11 @@ impl Game {
             heavy_bullet_texture: None,
             boss_bullet_texture: None,
             health_pack_texture: None,
+            audio: AudioManager::new(),
+            victory_sound: None,
+            death_sound: None,
+            master_volume: 1.0,
+            use_stat_allocation: false,
         };
         
         game.init_rogue_upgrades();
@@ -610,6 +933,10 @@ impl Game {
             RogueUpgrade::new(8, "Vibranium Armor", "DEF+3", "Reduces incoming damage by 3.", "◊", UpgradeRarity::Epic, None),
             RogueUpgrade::new(9, "Armor Piercing Shell", "PIERCE+1", "Bullets pierce through 1 additional enemy.", "►", UpgradeRarity::Rare, None),
             RogueUpgrade::new(10, "Bouncing Technology", "BOUNCE+1", "Bullets bounce to 1 additional target.", "◈", UpgradeRarity::Epic, None),
+            RogueUpgrade::new(11, "Homing Munitions", "HOMING", "Bullets are fired slow and curve in on the nearest enemy.", "➹", UpgradeRarity::Epic, None),
+            RogueUpgrade::new(12, "Cryo Rounds", "FREEZE+15%", "Bullets have a 15% chance to freeze a target, slowing it for 1.5s.", "❄", UpgradeRarity::Rare, Some(3)),
+            RogueUpgrade::new(13, "Static Discharge", "STUN+20%", "Bullets have a 20% chance to briefly stun a Boss on hit.", "⚡", UpgradeRarity::Epic, Some(3)),
+            RogueUpgrade::new(14, "Overdrive Module", "SKILL: OVERDRIVE", "Replaces your active skill with Overdrive Fire: doubles attack speed for 4s.", "◎", UpgradeRarity::Epic, Some(1)),
         ];
     }
 }
@@ -621,25 +948,34 @@ impl Game {
         self.state = GameState::Battle;
         self.start_time = Instant::now();
         
-        // 保存当前武器
-        let selected_weapon = self.player.weapon.clone();
-        
-        // 重新创建玩家但保留武器选择
+        // 保存当前武器库存
+        let weapons = self.player.weapons.clone();
+        let current_weapon = self.player.current_weapon;
+
+        // 重新创建玩家但保留武器库存
         self.player = Player::new();
-        self.player.weapon = selected_weapon;
-        
+        self.player.weapons = weapons;
+        self.player.current_weapon = current_weapon;
+
+        // 套用商店里买的永久强化
+        self.shop_upgrades.apply_to(&mut self.player);
+
         // 清空游戏状态
         self.enemies.clear();
-        self.bullets.clear();
+        self.bullet_manager.bullets.clear();
+        self.bullet_manager.new_bullets.clear();
         self.items.clear();
-        
+        self.formations.clear();
+        self.floating_texts.clear();
+
         // 重置本局统计数据
         self.current_session_coins = 0;
         self.current_session_exp = 0;
         self.enemies_defeated_this_session = 0;
         self.total_damage_dealt = 0;
         self.game_result = None;
-        
+        self.pause_started_at = None;
+
         // 重新初始化肉鸽升级
         self.init_rogue_upgrades();
     }
@@ -653,7 +989,7 @@ impl Game {
     }
     
     fn update_battle(&mut self, dt: f32) {
-        let elapsed = self.start_time.elapsed().as_secs_f32();
+        let elapsed = self.get_game_time();
         
         // 生成敌人
         self.spawn_enemies(elapsed);
@@ -669,7 +1005,10 @@ impl Game {
         
         // 更新道具
         self.update_items(dt);
-        
+
+        // 更新浮空提示文字
+        self.update_floating_texts(dt);
+
         // 碰撞检测
         self.check_collisions();
         
@@ -681,7 +1020,11 @@ impl Game {
         
         // 检查等级提升
         if self.player.experience >= self.player.experience_needed {
-            self.trigger_rogue_selection();
+            if self.use_stat_allocation {
+                self.trigger_stat_allocation();
+            } else {
+                self.trigger_rogue_selection();
+            }
         }
     }
     
@@ -718,15 +1061,21 @@ impl Game {
         // 生成普通敌人
         let scout_count = 3 + (elapsed / 60.0) as i32;
         let heavy_count = if elapsed < 20.0 { 0 } else { 1 + ((elapsed - 20.0) / 30.0) as i32 };
-        
+
         if (elapsed as i32) % 5 == 0 {
-            for _ in 0..scout_count {
-                let scout_pos = Vec2::new(
-                    self.rng.gen_range(center_left..center_right),
-                    self.rng.gen_range(center_top..center_bottom)
-                );
-                self.enemies.push(Enemy::new(EnemyType::Scout, scout_pos));
+            // 侦察机以编队整体出现，而不是各自散乱散布
+            let mut formation = FormationMaker::make_random(&mut self.rng, self.screen_width);
+            let member_count = (scout_count.max(1) as usize).min(FORMATION_MEMBER_MAX);
+
+            for slot in 0..member_count {
+                let spawn_pos = Vec2::new(formation.pivot.x, -20.0);
+                let mut scout = Enemy::new(EnemyType::Scout, spawn_pos);
+                scout.formation_slot = Some(slot);
+                formation.members.push(self.enemies.len());
+                self.enemies.push(scout);
             }
+
+            self.formations.push(formation);
         }
         
         if (elapsed as i32) % 10 == 0 && elapsed >= 20.0 {
@@ -742,64 +1091,186 @@ impl Game {
         self.last_spawn_time = Instant::now();
     }
     
-    fn update_player(&mut self, _dt: f32) {
+    fn update_player(&mut self, dt: f32) {
+        self.player.tick_status_effects(dt);
+
+        // 后坐力随时间衰减回零
+        self.player.recoil = (self.player.recoil - RECOIL_DECAY_PER_SEC * dt).max(0.0);
+
+        // SP回复和技能冷却
+        self.player.sp = (self.player.sp + self.player.sp_regen_rate() * dt).min(self.player.max_sp);
+        if let Some(skill) = self.player.skill.as_mut() {
+            skill.tick(dt);
+        }
+
         // 自动射击
         if self.player.can_shoot() {
             self.player_shoot();
             self.player.last_shot_time = Instant::now();
         }
     }
-    
-    fn player_shoot(&mut self) {
-        let bullet_count = self.player.get_total_bullet_count() as usize;
-        let attack_power = self.player.get_total_attack_power();
-        
-        // 创建带有玩家属性的子弹
-        let create_bullet = |pos: Vec2, vel: Vec2, damage: i32, player: &Player, rng: &mut ThreadRng, bullet_type: BulletType| {
-            let mut bullet = Bullet::new(pos, vel, damage, true, bullet_type);
-            bullet.piercing_count = match player.weapon.weapon_type {
-                WeaponType::Laser => 9999,
-                _ => player.piercing,
-            };
-            bullet.ricochet_count = player.ricochet;
-            bullet.burning_damage = player.burning_damage;
-            bullet.explosion_damage = player.explosion_damage;
-            
-            if rng.gen_range(0.0..1.0) < player.crit_rate {
-                bullet.damage = (bullet.damage as f32 * player.crit_damage) as i32;
-                bullet.is_crit = true;
-            }
-            bullet
-        };
-        
-        match self.player.weapon.weapon_type {
-            WeaponType::MachineGun => {
-                for i in 0..bullet_count {
+
+    /// 按键触发当前装备的主动技能：冷却和SP都满足才会释放，释放后扣SP并进入冷却
+    pub fn try_activate_skill(&mut self) {
+        let Some(skill) = self.player.skill.clone() else { return; };
+
+        if !skill.is_ready() || self.player.sp < skill.sp_cost {
+            return;
+        }
+
+        match skill.kind {
+            SkillKind::NovaBurst { radius, damage } => self.activate_nova_burst(radius, damage),
+            SkillKind::OverdriveFire { multiplier, duration } => self.activate_overdrive_fire(multiplier, duration),
+        }
+
+        self.player.sp -= skill.sp_cost;
+        if let Some(skill) = self.player.skill.as_mut() {
+            skill.cooldown_timer = skill.cooldown;
+        }
+    }
+
+    /// 清除玩家周围的敌方子弹，并对范围内敌人造成一次性伤害
+    fn activate_nova_burst(&mut self, radius: f32, damage: i32) {
+        let player_pos = self.player.position.clone();
+
+        self.bullet_manager.bullets.retain(|b| b.is_player_bullet || b.position.distance(&player_pos) > radius);
+
+        let mut enemies_to_remove = Vec::new();
+        for (enemy_idx, enemy) in self.enemies.iter_mut().enumerate() {
+            if enemy.health > 0 && enemy.position.distance(&player_pos) <= radius {
+                let actual_damage = enemy.take_damage(damage, Element::Neutral);
+                self.total_damage_dealt += actual_damage;
+                play_pooled(&self.audio.hit, self.master_volume, &mut self.rng);
+
+                let is_boss = enemy.enemy_type == EnemyType::Boss;
+                push_damage_floating_text(&mut self.floating_texts, actual_damage, enemy.position, is_boss);
+
+                if enemy.health <= 0 {
+                    let coins = enemy.get_drop_gold();
+                    let exp = enemy.get_drop_exp();
+
+                    self.current_session_coins += coins;
+                    self.current_session_exp += exp;
+                    self.enemies_defeated_this_session += 1;
+
+                    self.coins += coins;
+                    self.player.add_experience(exp);
+                    self.player.weapon_mut().add_experience((exp / 4).max(1) as u16);
+                    push_coin_floating_text(&mut self.floating_texts, coins, enemy.position);
+
+                    if enemy.enemy_type == EnemyType::Heavy {
+                        if self.rng.gen_range(0.0..1.0) < 0.4 {
+                            let health_pack = Item::new(
+                                ItemType::HealthPack,
+                                enemy.position.clone(),
+                                30
+                            );
+                            self.items.push(health_pack);
+                        }
+                    }
+
+                    let death_pool = if is_boss { &self.audio.boss_death } else { &self.audio.enemy_death };
+                    play_pooled(death_pool, self.master_volume, &mut self.rng);
+
+                    enemies_to_remove.push(enemy_idx);
+                }
+            }
+        }
+
+        self.remove_enemies(enemies_to_remove);
+    }
+
+    /// `duration`秒内让玩家的攻速乘以`multiplier`
+    fn activate_overdrive_fire(&mut self, multiplier: f32, duration: f32) {
+        self.player.apply_effect(StatusEffect::new(STATUS_ID_OVERDRIVE, duration, duration, EffectKind::AttackBoost(multiplier)));
+    }
+    
+    /// 追踪导弹逐帧索敌开销较高，这里借`BulletManager::count_bullets`给它单独设一个场上数量上限，
+    /// 超过上限时新开的追踪弹直接丢弃，避免升级叠满后拖累帧率
+    fn push_player_bullet(&mut self, bullet: Bullet) {
+        const MAX_HOMING_BULLETS: usize = 30;
+        if bullet.bullet_type == BulletType::PlayerHoming && self.bullet_manager.count_bullets(BulletType::PlayerHoming) >= MAX_HOMING_BULLETS {
+            return;
+        }
+        self.bullet_manager.push_bullet(bullet);
+    }
+
+    /// 播放单条可选音效（胜利/死亡），音量取`master_volume`；分类音效池走`play_pooled`，
+    /// 同样取`master_volume`，所以把它设成0就对所有音效生效
+    fn play_sound_effect(&self, sound: &Option<Sound>) {
+        if let Some(sound) = sound {
+            macroquad::audio::play_sound(sound, PlaySoundParams { looped: false, volume: self.master_volume });
+        }
+    }
+
+    fn player_shoot(&mut self) {
+        let bullet_count = self.player.get_total_bullet_count() as usize;
+        let attack_power = self.player.get_total_attack_power();
+
+        let (base_spread, recoil_factor, kick) = Weapon::spread_params(&self.player.weapon().weapon_type);
+        self.player.recoil += kick;
+        let max_deviation = (base_spread + recoil_factor * self.player.recoil) * self.player.spread_coefficient;
+
+        // 创建带有玩家属性的子弹，并叠加由精准度/后坐力决定的角度偏移；偏移/暴击判定改用
+        // 子弹自身从`BulletManager`派生出的种子化RNG，而不是全局`self.rng`，这样同一局的
+        // 弹道偏移和暴击结果只要固定起始种子就能完全重放
+        let create_bullet = |pos: Vec2, vel: Vec2, damage: i32, player: &Player, bullet_manager: &mut BulletManager, bullet_type: BulletType| {
+            let mut bullet = bullet_manager.create_bullet(pos, vel, damage, true, bullet_type);
+
+            let deviation_quarter_deg = bullet.rng.range(0.0, max_deviation) * if bullet.rng.next_f32() < 0.5 { 1.0 } else { -1.0 };
+            bullet.velocity = vel.rotated((deviation_quarter_deg * 0.25).to_radians());
+            bullet.base_heading = bullet.velocity.y.atan2(bullet.velocity.x);
+
+            bullet.piercing_count = match player.weapon().weapon_type {
+                WeaponType::Laser => 9999,
+                _ => player.piercing,
+            };
+            bullet.ricochet_count = player.ricochet;
+            bullet.burning_damage = player.burning_damage;
+            bullet.explosion_damage = player.explosion_damage;
+            bullet.element = player.bullet_element;
+
+            if player.homing_shots {
+                bullet.bullet_type = BulletType::PlayerHoming;
+                bullet.behavior = BulletBehavior::Homing { turn_rate: 3.0, accel: bullet.base_speed * 1.5 };
+                bullet.velocity = Vec2::new(bullet.velocity.x * 0.4, bullet.velocity.y * 0.4);
+            }
+
+            if bullet.rng.next_f32() < player.crit_rate {
+                bullet.damage = (bullet.damage as f32 * player.crit_damage) as i32;
+                bullet.is_crit = true;
+            }
+            bullet
+        };
+
+        match self.player.weapon().weapon_type {
+            WeaponType::MachineGun => {
+                for i in 0..bullet_count {
                     let offset_x = if i % 2 == 0 { -15.0 } else { 15.0 };
                     let bullet_pos = Vec2::new(self.player.position.x + offset_x, self.player.position.y - 10.0);
-                    let bullet_vel = Vec2::new(0.0, -self.player.weapon.bullet_speed * (1.0 + self.player.bullet_speed_bonus));
-                    
-                    let bullet = create_bullet(bullet_pos, bullet_vel, attack_power, &self.player, &mut self.rng, BulletType::PlayerMachineGun);
-                    self.bullets.push(bullet);
+                    let bullet_vel = Vec2::new(0.0, -self.player.weapon().bullet_speed * (1.0 + self.player.bullet_speed_bonus));
+
+                    let bullet = create_bullet(bullet_pos, bullet_vel, attack_power, &self.player, &mut self.bullet_manager, BulletType::PlayerMachineGun);
+                    self.push_player_bullet(bullet);
                 }
             },
             WeaponType::Shotgun => {
                 let total_angle = match bullet_count {
                     1 => 0.0, 2 => 30.0, 3 => 45.0, 4 => 60.0, _ => 60.0,
                 };
-                
+
                 let angle_step = if bullet_count <= 1 { 0.0 } else { total_angle / (bullet_count - 1) as f32 };
                 let start_angle = -total_angle / 2.0;
-                
+
                 for i in 0..bullet_count {
                     let angle = if bullet_count <= 1 { 0.0 } else { start_angle + angle_step * i as f32 };
                     let rad = angle.to_radians();
                     let bullet_pos = Vec2::new(self.player.position.x, self.player.position.y - 10.0);
-                    let speed = self.player.weapon.bullet_speed * (1.0 + self.player.bullet_speed_bonus);
+                    let speed = self.player.weapon().bullet_speed * (1.0 + self.player.bullet_speed_bonus);
                     let bullet_vel = Vec2::new(rad.sin() * speed, -rad.cos() * speed);
-                    
-                    let bullet = create_bullet(bullet_pos, bullet_vel, attack_power, &self.player, &mut self.rng, BulletType::PlayerShotgun);
-                    self.bullets.push(bullet);
+
+                    let bullet = create_bullet(bullet_pos, bullet_vel, attack_power, &self.player, &mut self.bullet_manager, BulletType::PlayerShotgun);
+                    self.push_player_bullet(bullet);
                 }
             },
             WeaponType::Laser => {
@@ -807,27 +1278,87 @@ impl Game {
                     let offset_x = if bullet_count > 1 { (i as f32 - (bullet_count - 1) as f32 / 2.0) * 5.0 } else { 0.0 };
                     let bullet_pos = Vec2::new(self.player.position.x + offset_x, self.player.position.y - 10.0);
                     let bullet_vel = Vec2::new(0.0, -8.0 * (1.0 + self.player.bullet_speed_bonus));
-                    
-                    let bullet = create_bullet(bullet_pos, bullet_vel, attack_power, &self.player, &mut self.rng, BulletType::PlayerLaser);
-                    self.bullets.push(bullet);
+
+                    let bullet = create_bullet(bullet_pos, bullet_vel, attack_power, &self.player, &mut self.bullet_manager, BulletType::PlayerLaser);
+                    self.push_player_bullet(bullet);
                 }
             }
         }
+
+        let weapon_type = self.player.weapon().weapon_type.clone();
+        self.play_weapon_fire_sound(&weapon_type);
     }
-    
+
     fn update_enemies(&mut self, dt: f32, elapsed: f32) {
         let mut new_enemies = Vec::new();
         let mut new_bullets = Vec::new();
         let screen_width = self.screen_width;
         let screen_height = self.screen_height;
         let player_position = self.player.position;
-        
-        for enemy in &mut self.enemies {
+
+        // 推进每个编队的整体角度，并算出每个成员的目标位置；椭圆编队绕完一整圈后集体脱队俯冲攻击
+        let mut formation_targets: Vec<(usize, Vec2)> = Vec::new();
+        let mut breakoff_members = Vec::new();
+        for formation in &mut self.formations {
+            formation.angle += formation.speed * dt;
+
+            if formation.template == FormationTemplate::EllipseOrbit && formation.angle >= std::f32::consts::TAU {
+                breakoff_members.extend(formation.members.drain(..));
+                continue;
+            }
+
+            let member_count = formation.members.len();
+            for (slot, &enemy_idx) in formation.members.iter().enumerate() {
+                formation_targets.push((enemy_idx, formation.slot_target(slot, member_count)));
+            }
+        }
+        self.formations.retain(|formation| !formation.members.is_empty());
+        for enemy_idx in breakoff_members {
+            if let Some(enemy) = self.enemies.get_mut(enemy_idx) {
+                enemy.formation_slot = None;
+            }
+        }
+
+        for (enemy_index, enemy) in self.enemies.iter_mut().enumerate() {
             enemy.movement_timer += dt;
-            
+            enemy.tick_status_effects(dt);
+
+            // 眩晕期间移动和开火都跳过，相当于本帧两者都冻结到0
+            if enemy.is_stunned {
+                continue;
+            }
+
+            // 冰冻减速：按状态效果算出的倍率缩放本帧的移动增量
+            let dt = dt * enemy.status_speed_multiplier;
+
             match enemy.enemy_type {
                 EnemyType::Scout => {
-                    enemy.position.y += enemy.velocity.y * dt * 100.0;
+                    let target = enemy.formation_slot.and_then(|_| {
+                        formation_targets.iter().find(|(idx, _)| *idx == enemy_index).map(|(_, pos)| *pos)
+                    });
+
+                    if let Some(target) = target {
+                        let dir = Vec2::new(target.x - enemy.position.x, target.y - enemy.position.y);
+                        let distance = (dir.x * dir.x + dir.y * dir.y).sqrt();
+
+                        if !enemy.has_reached_zone {
+                            // 飞入阶段：从屏幕外直奔编队目标槽位
+                            if distance < 5.0 {
+                                enemy.has_reached_zone = true;
+                            } else {
+                                let n = dir.normalize();
+                                enemy.position.x += n.x * 120.0 * dt;
+                                enemy.position.y += n.y * 120.0 * dt;
+                            }
+                        } else {
+                            // 编队阶段：平滑跟随队形目标点，而非走直线
+                            let lerp_factor = (5.0 * dt).min(1.0);
+                            enemy.position.x += dir.x * lerp_factor;
+                            enemy.position.y += dir.y * lerp_factor;
+                        }
+                    } else {
+                        enemy.position.y += enemy.velocity.y * dt * 100.0;
+                    }
                 },
                 EnemyType::Heavy => {
                     if !enemy.has_reached_zone {
@@ -841,58 +1372,40 @@ impl Game {
                         Self::update_heavy_movement(enemy, dt, screen_width, player_position);
                     }
                     
-                    if enemy.last_shot_time.elapsed().as_secs_f32() >= 1.0 {
+                    if enemy.last_shot_time.elapsed().as_secs_f32() >= 1.0 / enemy.status_speed_multiplier.max(0.05) {
                         let attack_pattern = (enemy.movement_timer as i32) % 4;
-                        
+                        let bullet_pos = Vec2::new(enemy.position.x, enemy.position.y + 20.0);
+                        let table = Self::heavy_attack_emitters();
+
                         match attack_pattern {
                             0 => {
                                 if elapsed >= 90.0 {
-                                    let target_dir = Vec2::new(
-                                        player_position.x - enemy.position.x,
-                                        player_position.y - enemy.position.y
-                                    ).normalize();
-                                    
-                                    for i in 0..3 {
-                                        let spread_angle = (-10.0 + i as f32 * 10.0).to_radians();
-                                        let bullet_vel = Vec2::new(
-                                            target_dir.x * 2.5 + spread_angle.sin() * 0.5,
-                                            target_dir.y * 2.5 + spread_angle.cos() * 0.5
-                                        );
-                                        let bullet_pos = Vec2::new(enemy.position.x, enemy.position.y + 20.0);
-                                        new_bullets.push(Bullet::new(bullet_pos, bullet_vel, enemy.bullet_damage + 1, false, BulletType::EnemyHeavy));
-                                    }
+                                    let (_, emitter) = &table[0];
+                                    new_bullets.extend(emitter.emit(bullet_pos, player_position, enemy.bullet_damage + 1, BulletType::EnemyHeavy));
                                 }
                             },
                             1 => {
                                 if elapsed >= 90.0 {
-                                    for i in 0..5 {
-                                        let angle = (-30.0 + i as f32 * 15.0).to_radians();
-                                        let bullet_pos = Vec2::new(enemy.position.x, enemy.position.y + 20.0);
-                                        let bullet_vel = Vec2::new(angle.sin() * 2.0, angle.cos() * 2.0 + 1.0);
-                                        new_bullets.push(Bullet::new(bullet_pos, bullet_vel, enemy.bullet_damage, false, BulletType::EnemyGeneric));
-                                    }
+                                    let (_, emitter) = &table[1];
+                                    new_bullets.extend(emitter.emit(bullet_pos, player_position, enemy.bullet_damage, BulletType::EnemyGeneric));
                                 }
                             },
                             2 => {
-                                let bullet_pos1 = Vec2::new(enemy.position.x - 10.0, enemy.position.y + 20.0);
-                                let bullet_pos2 = Vec2::new(enemy.position.x + 10.0, enemy.position.y + 20.0);
-                                let bullet_vel = Vec2::new(0.0, 3.0);
-                                new_bullets.push(Bullet::new(bullet_pos1, bullet_vel, enemy.bullet_damage, false, BulletType::EnemyGeneric));
-                                new_bullets.push(Bullet::new(bullet_pos2, bullet_vel, enemy.bullet_damage, false, BulletType::EnemyGeneric));
+                                // 抛物线迫击炮弹：受重力下坠，触地带能量损耗地弹起一次，寿命耗尽后自行消散
+                                let (_, emitter) = &table[2];
+                                let mut mortar_shells = emitter.emit(bullet_pos, player_position, enemy.bullet_damage, BulletType::EnemyGeneric);
+                                for shell in &mut mortar_shells {
+                                    shell.gravity = 4.0;
+                                    shell.max_fall_speed = 3.0;
+                                    shell.ricochet_count = 1;
+                                    shell.life_frames = 240;
+                                }
+                                new_bullets.extend(mortar_shells);
                             },
                             3 => {
-                                let predict_pos = Vec2::new(
-                                    player_position.x,
-                                    player_position.y + 50.0
-                                );
-                                let target_dir = Vec2::new(
-                                    predict_pos.x - enemy.position.x,
-                                    predict_pos.y - enemy.position.y
-                                ).normalize();
-                                
-                                let bullet_pos = Vec2::new(enemy.position.x, enemy.position.y + 20.0);
-                                let bullet_vel = Vec2::new(target_dir.x * 3.0, target_dir.y * 3.0);
-                                new_bullets.push(Bullet::new(bullet_pos, bullet_vel, enemy.bullet_damage + 2, false, BulletType::EnemyBoss));
+                                let (_, emitter) = &table[3];
+                                let predict_pos = Vec2::new(player_position.x, player_position.y + 50.0);
+                                new_bullets.extend(emitter.emit(bullet_pos, predict_pos, enemy.bullet_damage + 2, BulletType::EnemyBoss));
                             },
                             _ => {}
                         }
@@ -902,7 +1415,7 @@ impl Game {
                 EnemyType::Carrier => {
                     enemy.position.y += enemy.velocity.y * dt * 100.0;
                     
-                    if enemy.last_shot_time.elapsed().as_secs_f32() >= 5.0 {
+                    if enemy.last_shot_time.elapsed().as_secs_f32() >= 5.0 / enemy.status_speed_multiplier.max(0.05) {
                         let scout_pos = Vec2::new(enemy.position.x, enemy.position.y + 30.0);
                         new_enemies.push(Enemy::new(EnemyType::Scout, scout_pos));
                         enemy.last_shot_time = Instant::now();
@@ -920,33 +1433,29 @@ impl Game {
                         Self::update_boss_movement(enemy, dt, screen_width);
                     }
                     
-                    let boss_bullets = Self::update_boss_and_get_bullets(enemy, elapsed);
+                    let boss_bullets = Self::update_boss_and_get_bullets(enemy, player_position);
                     new_bullets.extend(boss_bullets);
                 }
             }
         }
         
         self.enemies.extend(new_enemies);
-        self.bullets.extend(new_bullets);
-        
-        self.enemies.retain(|enemy| {
+        self.bullet_manager.push_bullets(new_bullets);
+
+        let leaving_indices: Vec<usize> = self.enemies.iter().enumerate().filter(|(_, enemy)| {
+            let on_screen = || {
+                enemy.position.y < screen_height + 50.0 &&
+                enemy.position.x > -50.0 &&
+                enemy.position.x < screen_width + 50.0
+            };
             match enemy.enemy_type {
                 EnemyType::Heavy | EnemyType::Boss => {
-                    if enemy.has_reached_zone {
-                        enemy.health > 0
-                    } else {
-                        enemy.position.y < screen_height + 50.0 && 
-                        enemy.position.x > -50.0 && 
-                        enemy.position.x < screen_width + 50.0
-                    }
+                    if enemy.has_reached_zone { enemy.health <= 0 } else { !on_screen() }
                 },
-                _ => {
-                    enemy.position.y < screen_height + 50.0 && 
-                    enemy.position.x > -50.0 && 
-                    enemy.position.x < screen_width + 50.0
-                }
+                _ => !on_screen(),
             }
-        });
+        }).map(|(idx, _)| idx).collect();
+        self.remove_enemies(leaving_indices);
     }
     
     fn update_heavy_movement(enemy: &mut Enemy, dt: f32, screen_width: f32, player_position: Vec2) {
@@ -1048,268 +1557,310 @@ impl Game {
         enemy.position.y = base_y + float_amplitude * (enemy.movement_timer * float_frequency).sin();
     }
     
-    fn update_boss_and_get_bullets(boss: &mut Enemy, _elapsed: f32) -> Vec<Bullet> {
+    fn update_boss_and_get_bullets(boss: &mut Enemy, player_pos: Vec2) -> Vec<Bullet> {
         let mut new_bullets = Vec::new();
-        let boss_time = boss.spawn_time.elapsed().as_secs_f32();
-        
-        if boss.special_state == 1 {
-            if boss.last_shot_time.elapsed().as_secs_f32() >= 3.0 {
-                let attack_cycle = (boss_time as i32) % 6;
-                
-                match attack_cycle {
-                    0 => {
-                        for i in 0..24 {
-                            let angle = (i as f32 * 15.0).to_radians();
-                            let bullet_pos = Vec2::new(boss.position.x, boss.position.y + 50.0);
-                            let bullet_vel = Vec2::new(angle.cos() * 1.5, angle.sin() * 1.5);
-                            new_bullets.push(Bullet::new(bullet_pos, bullet_vel, boss.bullet_damage, false, BulletType::EnemyBoss));
-                        }
-                    },
-                    1..=5 => {
-                        // 其他攻击模式...
-                        for i in 0..12 {
-                            let angle = (i as f32 * 30.0).to_radians();
-                            let bullet_pos = Vec2::new(boss.position.x, boss.position.y + 50.0);
-                            let bullet_vel = Vec2::new(angle.cos() * 2.0, angle.sin() * 2.0);
-                            new_bullets.push(Bullet::new(bullet_pos, bullet_vel, boss.bullet_damage, false, BulletType::EnemyBoss));
-                        }
-                    },
-                    _ => {}
-                }
-                boss.last_shot_time = Instant::now();
-            }
-        } else if boss.special_state == 2 {
-            if boss.is_invincible && boss.spawn_time.elapsed().as_secs_f32() >= 5.0 {
-                boss.is_invincible = false;
-            }
-            
-            if boss.last_shot_time.elapsed().as_secs_f32() >= 2.0 {
-                for i in 0..32 {
-                    let angle = (i as f32 * 11.25).to_radians();
-                    let bullet_pos = Vec2::new(boss.position.x, boss.position.y + 50.0);
-                    let bullet_vel = Vec2::new(angle.cos() * 2.5, angle.sin() * 2.5);
-                    new_bullets.push(Bullet::new(bullet_pos, bullet_vel, 15, false, BulletType::EnemyBoss));
-                }
-                boss.last_shot_time = Instant::now();
-            }
+        let bullet_pos = Vec2::new(boss.position.x, boss.position.y + 50.0);
+        let special_state = boss.special_state;
+
+        let (pattern, damage, runner): (Vec<PatternAction>, i32, &mut PatternRunner) = match special_state {
+            1 => (Self::boss_phase1_pattern(), boss.bullet_damage, &mut boss.phase1_runner),
+            2 => (Self::boss_phase2_pattern(), 15, &mut boss.phase2_runner),
+            _ => return new_bullets,
+        };
+
+        let signal = runner.step(&pattern, bullet_pos, player_pos, damage, &mut new_bullets);
+        if signal.vanish {
+            boss.health = 0;
         }
-        
-        new_bullets
-    }
-    
-    fn update_bullets(&mut self, dt: f32) {
-        for bullet in &mut self.bullets {
-            bullet.position.x += bullet.velocity.x * dt * 100.0;
-            bullet.position.y += bullet.velocity.y * dt * 100.0;
-            
-            if bullet.ricochet_count > 0 {
-                let mut bounced = false;
-                if bullet.position.x <= 0.0 || bullet.position.x >= self.screen_width {
-                    bullet.velocity.x = -bullet.velocity.x;
-                    bullet.ricochet_count -= 1;
-                    bounced = true;
-                }
-                if bullet.position.y <= 0.0 || bullet.position.y >= self.screen_height {
-                    bullet.velocity.y = -bullet.velocity.y;
-                    bullet.ricochet_count -= 1;
-                    bounced = true;
-                }
-                if bounced {
-                    bullet.position.x = bullet.position.x.clamp(0.0, self.screen_width);
-                    bullet.position.y = bullet.position.y.clamp(0.0, self.screen_height);
-                    bullet.hit_enemies.clear();
-                }
-            }
+        if let Some(invincible) = signal.set_invincible {
+            boss.is_invincible = invincible;
         }
-        
-        self.bullets.retain(|bullet| {
-            if bullet.ricochet_count > 0 {
-                true
-            } else {
-                bullet.position.y > -50.0 && bullet.position.y < self.screen_height + 50.0 &&
-                bullet.position.x > -50.0 && bullet.position.x < self.screen_width + 50.0
-            }
-        });
+
+        new_bullets
     }
-    
-    fn update_items(&mut self, dt: f32) {
-        for item in &mut self.items {
-            item.position.y += item.velocity.y * dt * 50.0;
-        }
-        
-        self.items.retain(|item| item.position.y < self.screen_height + 50.0);
+
+    /// 重甲舰四段攻击节拍的弹幕表：时机对应`attack_pattern`循环中的节拍序号
+    fn heavy_attack_emitters() -> Vec<(f32, BulletEmitter)> {
+        vec![
+            (0.0, BulletEmitter::new(3, 1, 2.5, 0.0, 90.0, 20.0, AimMode::AtPlayer)),
+            (1.0, BulletEmitter::new(5, 1, 2.0, 0.0, 90.0, 60.0, AimMode::Fixed)),
+            (2.0, BulletEmitter::new(2, 1, 3.0, 0.0, 90.0, 6.0, AimMode::Fixed)),
+            (3.0, BulletEmitter::new(1, 1, 3.0, 0.0, 90.0, 0.0, AimMode::AtPlayer)),
+        ]
+    }
+
+    /// Boss第一阶段的弹幕脚本：先打一圈24连环，等3秒后再循环"12连环+等3秒"五轮，然后从头开始
+    fn boss_phase1_pattern() -> Vec<PatternAction> {
+        vec![PatternAction::Repeat {
+            times: i32::MAX,
+            body: vec![
+                PatternAction::Repeat {
+                    times: 24,
+                    body: vec![PatternAction::Fire {
+                        dir: DirSpec { mode: ValueMode::Sequence, value: 15.0 },
+                        speed: SpeedSpec { mode: ValueMode::Absolute, value: 1.5 },
+                        bullet_type: BulletType::EnemyBoss,
+                    }],
+                },
+                PatternAction::Wait(180),
+                PatternAction::Repeat {
+                    times: 5,
+                    body: vec![
+                        PatternAction::Repeat {
+                            times: 12,
+                            body: vec![PatternAction::Fire {
+                                dir: DirSpec { mode: ValueMode::Sequence, value: 30.0 },
+                                speed: SpeedSpec { mode: ValueMode::Absolute, value: 2.0 },
+                                bullet_type: BulletType::EnemyBoss,
+                            }],
+                        },
+                        PatternAction::Wait(180),
+                    ],
+                },
+            ],
+        }]
+    }
+
+    /// Boss第二阶段（残血狂暴）的弹幕脚本：先维持5秒无敌窗口，窗口结束后每2秒打一圈32连环
+    fn boss_phase2_pattern() -> Vec<PatternAction> {
+        vec![
+            PatternAction::Wait(300),
+            PatternAction::SetInvincible(false),
+            PatternAction::Repeat {
+                times: i32::MAX,
+                body: vec![
+                    PatternAction::Repeat {
+                        times: 32,
+                        body: vec![PatternAction::Fire {
+                            dir: DirSpec { mode: ValueMode::Sequence, value: 11.25 },
+                            speed: SpeedSpec { mode: ValueMode::Absolute, value: 2.5 },
+                            bullet_type: BulletType::EnemyBoss,
+                        }],
+                    },
+                    PatternAction::Wait(120),
+                ],
+            },
+        ]
     }
-    
-    fn check_item_collisions(&mut self) {
-        let mut items_to_remove = Vec::new();
-        
-        for (item_idx, item) in self.items.iter().enumerate() {
-            let distance = item.position.distance(&self.player.position);
-            if distance < 25.0 {
-                match item.item_type {
-                    ItemType::HealthPack => {
-                        self.player.health = (self.player.health + item.value).min(self.player.max_health);
-                    }
-                }
-                items_to_remove.push(item_idx);
-            }
-        }
-        
-        items_to_remove.sort_unstable();
-        items_to_remove.reverse();
-        for idx in items_to_remove {
-            if idx < self.items.len() {
-                self.items.remove(idx);
-            }
-        }
+
+    fn update_bullets(&mut self, dt: f32) {
+        let player_position = self.player.position;
+        let screen_width = self.screen_width;
+        let screen_height = self.screen_height;
+
+        let report = self.bullet_manager.tick(dt, &self.enemies, player_position, screen_width, screen_height, &mut self.items);
+        self.apply_bullet_hit_report(report);
     }
-    
-    fn check_collisions(&mut self) {
-        let mut bullets_to_remove = Vec::new();
+
+    /// 把`BulletManager::tick`里检测到的玩家子弹命中结算到敌人身上：扣血、燃烧、爆炸溅射、
+    /// 掉落与击杀统计，以及击杀后敌人/子弹索引列表的重新对齐。这部分之所以留在`Game`而不是
+    /// `BulletManager`，是因为它要改动的是`enemies`、掉落物和玩家经验，都不归子弹子系统所有
+    fn apply_bullet_hit_report(&mut self, report: BulletHitReport) {
         let mut enemies_to_remove = Vec::new();
-        let mut explosion_damages = Vec::new();
-        let mut enemy_bullet_hits = Vec::new();
-        let mut bullet_piercing_updates = Vec::new();
-        let mut bullet_hit_updates = Vec::new();
-        
-        // 子弹与敌人碰撞
-        for (bullet_idx, bullet) in self.bullets.iter().enumerate() {
-            if !bullet.is_player_bullet {
-                continue;
+
+        for (enemy_idx, effect) in report.enemy_burning_applies {
+            if let Some(enemy) = self.enemies.get_mut(enemy_idx) {
+                enemy.apply_effect(effect);
             }
-            
-            let mut should_remove_bullet = false;
-            let mut new_hit_enemies = bullet.hit_enemies.clone();
-            
-            for (enemy_idx, enemy) in self.enemies.iter().enumerate() {
-                if enemy.health <= 0 || bullet.hit_enemies.contains(&enemy_idx) {
-                    continue;
-                }
-                
-                let distance = bullet.position.distance(&enemy.position);
-                if distance < 30.0 {
-                    new_hit_enemies.push(enemy_idx);
-                    
-                    let mut damage = bullet.damage;
-                    if bullet.burning_damage > 0 {
-                        damage += bullet.burning_damage;
-                    }
-                    
-                    enemy_bullet_hits.push((enemy_idx, damage));
-                    self.total_damage_dealt += damage;
-                    
-                    if bullet.explosion_damage > 0.0 {
-                        let explosion_dmg = (damage as f32 * bullet.explosion_damage) as i32;
-                        explosion_damages.push((enemy.position, explosion_dmg));
-                    }
-                    
-                    if bullet.piercing_count != 9999 && bullet.piercing_count > 0 {
-                        bullet_piercing_updates.push((bullet_idx, bullet.piercing_count - 1));
-                        if bullet.piercing_count - 1 <= 0 {
-                            should_remove_bullet = true;
-                        }
-                    } else if bullet.piercing_count == 0 {
-                        should_remove_bullet = true;
+        }
+
+        for (enemy_idx, damage, element, is_crit) in report.enemy_hits {
+            if let Some(enemy) = self.enemies.get_mut(enemy_idx) {
+                let actual_damage = enemy.take_damage(damage, element);
+                self.total_damage_dealt += actual_damage;
+                play_pooled(&self.audio.hit, self.master_volume, &mut self.rng);
+
+                let is_boss = enemy.enemy_type == EnemyType::Boss;
+                push_damage_floating_text(&mut self.floating_texts, actual_damage, enemy.position, is_boss || is_crit);
+
+                if enemy.health > 0 {
+                    // 冰冻/眩晕的触发各自走独立的命中概率和每敌人的触发冷却，避免一把多连发的武器
+                    // 在冷却期内反复刷新持续时间
+                    if enemy.freeze_proc_cooldown <= 0.0 && self.rng.gen_range(0.0..1.0) < self.player.freeze_on_hit_chance {
+                        enemy.apply_effect(StatusEffect::new(STATUS_ID_FREEZE, 1.5, 1.5, EffectKind::Slow(0.4)));
+                        enemy.freeze_proc_cooldown = 1.5;
                     }
-                    
-                    if bullet.piercing_count == 0 {
-                        break;
+
+                    if enemy.enemy_type == EnemyType::Boss
+                        && enemy.stun_proc_cooldown <= 0.0
+                        && self.rng.gen_range(0.0..1.0) < self.player.boss_stun_on_hit_chance
+                    {
+                        enemy.apply_effect(StatusEffect::new(STATUS_ID_STUN, 0.6, 0.6, EffectKind::Stun));
+                        enemy.stun_proc_cooldown = 2.0;
                     }
                 }
-            }
-            
-            if new_hit_enemies.len() > bullet.hit_enemies.len() {
-                bullet_hit_updates.push((bullet_idx, new_hit_enemies));
-            }
-            
-            if should_remove_bullet {
-                bullets_to_remove.push(bullet_idx);
-            }
-        }
-        
-        // 更新子弹数据
-        for (bullet_idx, new_hit_list) in bullet_hit_updates {
-            if let Some(bullet) = self.bullets.get_mut(bullet_idx) {
-                bullet.hit_enemies = new_hit_list;
-            }
-        }
-        
-        for (bullet_idx, new_piercing) in bullet_piercing_updates {
-            if let Some(bullet) = self.bullets.get_mut(bullet_idx) {
-                bullet.piercing_count = new_piercing;
-            }
-        }
-        
-        // 应用伤害
-        for (enemy_idx, damage) in enemy_bullet_hits {
-            if let Some(enemy) = self.enemies.get_mut(enemy_idx) {
-                enemy.take_damage(damage);
-                
+
                 if enemy.health <= 0 {
                     let coins = enemy.get_drop_gold();
                     let exp = enemy.get_drop_exp();
-                    
+
                     self.current_session_coins += coins;
                     self.current_session_exp += exp;
                     self.enemies_defeated_this_session += 1;
-                    
+
                     self.coins += coins;
                     self.player.add_experience(exp);
-                    
-                    // 重甲舰掉落道具
+                    self.player.weapon_mut().add_experience((exp / 4).max(1) as u16);
+                    push_coin_floating_text(&mut self.floating_texts, coins, enemy.position);
+
                     if enemy.enemy_type == EnemyType::Heavy {
                         if self.rng.gen_range(0.0..1.0) < 0.4 {
                             let health_pack = Item::new(
-                                ItemType::HealthPack, 
-                                enemy.position.clone(), 
+                                ItemType::HealthPack,
+                                enemy.position.clone(),
                                 30
                             );
                             self.items.push(health_pack);
                         }
                     }
-                    
+
+                    let is_boss = enemy.enemy_type == EnemyType::Boss;
+                    let death_pool = if is_boss { &self.audio.boss_death } else { &self.audio.enemy_death };
+                    play_pooled(death_pool, self.master_volume, &mut self.rng);
+
                     enemies_to_remove.push(enemy_idx);
                 }
             }
         }
-        
-        // 处理爆炸效果
-        for (explosion_pos, explosion_dmg) in explosion_damages {
+
+        for (explosion_pos, explosion_dmg, element) in report.explosion_damages {
             for (enemy_idx, enemy) in self.enemies.iter_mut().enumerate() {
                 if enemy.health > 0 && enemy.position.distance(&explosion_pos) < 50.0 {
-                    enemy.take_damage(explosion_dmg);
-                    self.total_damage_dealt += explosion_dmg;
-                    
+                    let actual_explosion_damage = enemy.take_damage(explosion_dmg, element);
+                    self.total_damage_dealt += actual_explosion_damage;
+                    play_pooled(&self.audio.hit, self.master_volume, &mut self.rng);
+
+                    let explosion_is_boss = enemy.enemy_type == EnemyType::Boss;
+                    push_damage_floating_text(&mut self.floating_texts, actual_explosion_damage, enemy.position, explosion_is_boss);
+
                     if enemy.health <= 0 {
                         let coins = enemy.get_drop_gold();
                         let exp = enemy.get_drop_exp();
-                        
+
                         self.current_session_coins += coins;
                         self.current_session_exp += exp;
                         self.enemies_defeated_this_session += 1;
-                        
+
                         self.coins += coins;
                         self.player.add_experience(exp);
-                        
+                        self.player.weapon_mut().add_experience((exp / 4).max(1) as u16);
+                        push_coin_floating_text(&mut self.floating_texts, coins, enemy.position);
+
                         if enemy.enemy_type == EnemyType::Heavy {
                             if self.rng.gen_range(0.0..1.0) < 0.4 {
                                 let health_pack = Item::new(
-                                    ItemType::HealthPack, 
-                                    enemy.position.clone(), 
+                                    ItemType::HealthPack,
+                                    enemy.position.clone(),
                                     30
                                 );
                                 self.items.push(health_pack);
                             }
                         }
-                        
+
+                        let is_boss = enemy.enemy_type == EnemyType::Boss;
+                        let death_pool = if is_boss { &self.audio.boss_death } else { &self.audio.enemy_death };
+                        play_pooled(death_pool, self.master_volume, &mut self.rng);
+
                         enemies_to_remove.push(enemy_idx);
                     }
                 }
             }
         }
+
+        self.remove_enemies(enemies_to_remove);
+    }
+
+    /// 按索引移除敌人，并把`bullet.hit_enemies`/`formation.members`里引用到的索引同步下移，
+    /// 否则移除中间的敌人会让后面敌人的索引错位到別的目标上。任何移除`self.enemies`的地方
+    /// 都应该走这里，而不是直接调用`Vec::remove`或`retain`
+    fn remove_enemies(&mut self, mut indices: Vec<usize>) {
+        indices.sort_unstable();
+        indices.dedup();
+        indices.reverse();
+
+        for idx in indices {
+            if idx < self.enemies.len() {
+                self.enemies.remove(idx);
+
+                for bullet in &mut self.bullet_manager.bullets {
+                    bullet.hit_enemies.retain(|&enemy_idx| enemy_idx != idx);
+                    for hit_idx in &mut bullet.hit_enemies {
+                        if *hit_idx > idx {
+                            *hit_idx -= 1;
+                        }
+                    }
+                }
+
+                for formation in &mut self.formations {
+                    formation.members.retain(|&enemy_idx| enemy_idx != idx);
+                    for member_idx in &mut formation.members {
+                        if *member_idx > idx {
+                            *member_idx -= 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.formations.retain(|formation| !formation.members.is_empty());
+    }
+    
+    fn update_items(&mut self, dt: f32) {
+        for item in &mut self.items {
+            item.position.y += item.velocity.y * dt * 50.0;
+        }
+
+        let screen_height = self.screen_height;
+        self.items.retain(|item| match item.item_type {
+            ItemType::Dissipation => item.spawn_time.elapsed().as_secs_f32() < 0.4,
+            ItemType::HealthPack => item.position.y < screen_height + 50.0,
+        });
+    }
+
+    fn update_floating_texts(&mut self, dt: f32) {
+        for floating_text in &mut self.floating_texts {
+            floating_text.position.x += floating_text.velocity.x * dt;
+            floating_text.position.y += floating_text.velocity.y * dt;
+        }
+
+        self.floating_texts.retain(|floating_text| floating_text.spawn_time.elapsed().as_secs_f32() < FLOATING_TEXT_LIFETIME);
+    }
+
+    fn check_item_collisions(&mut self) {
+        let mut items_to_remove = Vec::new();
+        
+        for (item_idx, item) in self.items.iter().enumerate() {
+            let distance = item.position.distance(&self.player.position);
+            if distance < 25.0 {
+                match item.item_type {
+                    ItemType::HealthPack => {
+                        self.player.health = (self.player.health + item.value).min(self.player.max_health);
+                        play_pooled(&self.audio.item_pickup, self.master_volume, &mut self.rng);
+                        self.floating_texts.push(FloatingText::new(format!("+{} HP", item.value), item.position, GREEN));
+                        items_to_remove.push(item_idx);
+                    }
+                    ItemType::Dissipation => {},
+                }
+            }
+        }
         
+        items_to_remove.sort_unstable();
+        items_to_remove.reverse();
+        for idx in items_to_remove {
+            if idx < self.items.len() {
+                self.items.remove(idx);
+            }
+        }
+    }
+    
+    /// 玩家子弹与敌人的碰撞检测已经挪到`BulletManager::tick`/`apply_bullet_hit_report`里，
+    /// 随子弹的移动、寿命结算一起做，避免同一帧内子弹数据被检测两次；这里只剩下
+    /// 敌人子弹命中玩家，以及敌人本体与玩家的接触伤害
+    fn check_collisions(&mut self) {
+        let mut bullets_to_remove = Vec::new();
+
         // 敌人子弹与玩家碰撞
-        for (bullet_idx, bullet) in self.bullets.iter().enumerate() {
+        for (bullet_idx, bullet) in self.bullet_manager.bullets.iter().enumerate() {
             if bullet.is_player_bullet {
                 continue;
             }
@@ -1319,7 +1870,7 @@ impl Game {
                 bullets_to_remove.push(bullet_idx);
             }
         }
-        
+
         // 敌人与玩家碰撞
         for enemy in &self.enemies {
             let distance = enemy.position.distance(&self.player.position);
@@ -1327,31 +1878,13 @@ impl Game {
                 self.player.take_damage(enemy.collision_damage);
             }
         }
-        
-        // 移除子弹和敌人
+
+        // 移除子弹
         bullets_to_remove.sort_unstable();
         bullets_to_remove.reverse();
         for idx in bullets_to_remove {
-            if idx < self.bullets.len() {
-                self.bullets.remove(idx);
-            }
-        }
-        
-        enemies_to_remove.sort_unstable();
-        enemies_to_remove.dedup();
-        enemies_to_remove.reverse();
-        for idx in enemies_to_remove {
-            if idx < self.enemies.len() {
-                self.enemies.remove(idx);
-                
-                for bullet in &mut self.bullets {
-                    bullet.hit_enemies.retain(|&enemy_idx| enemy_idx != idx);
-                    for hit_idx in &mut bullet.hit_enemies {
-                        if *hit_idx > idx {
-                            *hit_idx -= 1;
-                        }
-                    }
-                }
+            if idx < self.bullet_manager.bullets.len() {
+                self.bullet_manager.bullets.remove(idx);
             }
         }
     }
@@ -1385,27 +1918,40 @@ impl Game {
         
         game_result.coins_earned = self.current_session_coins;
         game_result.experience_gained = self.current_session_exp;
-        
+
+        if self.user.is_logged_in {
+            if let Err(e) = game_result.save(&self.pool, &self.user.username) {
+                println!("战绩保存失败: {}", e);
+            }
+        }
+
         self.game_result = Some(game_result);
-        
+
         if victory {
             self.wins += 1;
+            self.play_sound_effect(&self.victory_sound);
+        } else {
+            self.play_sound_effect(&self.death_sound);
         }
-        
+
         self.state = GameState::GameOver;
         self.reset_game_progress();
     }
     
     fn reset_game_progress(&mut self) {
-        self.coins = 0;
-        
-        let current_weapon = self.player.weapon.clone();
+        // 金币是跨局持久化的商店货币（类似`wins`），不在这里清零
+
+        let weapons = self.player.weapons.clone();
+        let current_weapon = self.player.current_weapon;
         self.player = Player::new();
-        self.player.weapon = current_weapon;
-        
+        self.player.weapons = weapons;
+        self.player.current_weapon = current_weapon;
+
         self.enemies.clear();
-        self.bullets.clear();
-        
+        self.bullet_manager.bullets.clear();
+        self.bullet_manager.new_bullets.clear();
+        self.formations.clear();
+
         self.current_session_coins = 0;
         self.current_session_exp = 0;
         self.enemies_defeated_this_session = 0;
@@ -1417,7 +1963,7 @@ impl Game {
     pub fn get_game_result(&self) -> Option<&GameResult> {
         self.game_result.as_ref()
     }
-    
+
     fn trigger_rogue_selection(&mut self) {
         self.current_rogue_options = self.get_random_rogue_options();
         
@@ -1474,20 +2020,22 @@ impl Game {
     
     fn apply_upgrade_and_complete(&mut self, upgrade: RogueUpgrade) {
         self.apply_rogue_upgrade(upgrade.id);
-        
+
         self.player.rogue_upgrades.push(upgrade.clone());
-        
+
         if let Some(available_upgrade) = self.available_upgrades.iter_mut().find(|u| u.id == upgrade.id) {
             available_upgrade.current_selections += 1;
-            
+
             if let Some(max) = available_upgrade.max_selections {
                 if available_upgrade.current_selections >= max {
                     self.available_upgrades.retain(|u| u.id != upgrade.id);
                 }
             }
         }
+
+        self.play_rogue_select_sound();
     }
-    
+
     fn apply_rogue_upgrade(&mut self, upgrade_id: u32) {
         match upgrade_id {
             0 => {
@@ -1495,25 +2043,38 @@ impl Game {
                 self.player.health += 3;
             },
             1 => self.player.attack_power_bonus += 2,
-            2 => self.player.crit_rate += 0.1,
+            2 => {
+                self.player.crit_rate += 0.1;
+                self.player.spread_coefficient *= 0.7;
+            },
             3 => self.player.crit_damage += 0.2,
             4 => self.player.bullet_count_bonus += 1,
             5 => self.player.explosion_damage += 0.3,
-            6 => self.player.burning_damage += 2,
+            6 => {
+                self.player.burning_damage += 2;
+                self.player.bullet_element = Element::Fire;
+            },
             7 => {
                 self.player.bullet_speed_bonus += 0.3;
-                self.player.weapon.attack_speed *= 1.3;
+                for weapon in &mut self.player.weapons {
+                    weapon.attack_speed *= 1.3;
+                }
             },
             8 => self.player.damage_reduction += 3,
             9 => self.player.piercing += 1,
             10 => self.player.ricochet += 1,
+            11 => self.player.homing_shots = true,
+            12 => self.player.freeze_on_hit_chance += 0.15,
+            13 => self.player.boss_stun_on_hit_chance += 0.2,
+            14 => self.player.skill = Some(ActiveSkill::new(10.0, 60.0, SkillKind::OverdriveFire { multiplier: 2.0, duration: 4.0 })),
             _ => {}
         }
     }
     
     fn complete_rogue_selection(&mut self) {
         self.player.level_up();
-        
+        self.play_level_up_sound();
+
         self.current_rogue_options.clear();
         
         if self.player.experience >= self.player.experience_needed {
@@ -1527,16 +2088,66 @@ impl Game {
         if self.state != GameState::RogueSelection || self.rogue_auto_selected {
             return;
         }
-        
+
         if option_index < self.current_rogue_options.len() {
             let selected_upgrade = self.current_rogue_options[option_index].clone();
-            
+
             self.apply_upgrade_and_complete(selected_upgrade);
-            
+
             self.complete_rogue_selection();
         }
     }
-    
+
+    /// 进入手动属性分配界面，给玩家一个待投入的点数
+    fn trigger_stat_allocation(&mut self) {
+        self.player.stat_allocation.available_points += 1;
+        self.state = GameState::StatAllocation;
+    }
+
+    /// 按卡片索引把当前点数投入某条职业路线，立即套用该路线每点的固定增量
+    pub fn allocate_stat_point(&mut self, index: usize) {
+        if self.state != GameState::StatAllocation || self.player.stat_allocation.available_points <= 0 {
+            return;
+        }
+
+        if index >= ClassRoute::ALL.len() {
+            return;
+        }
+
+        let route = ClassRoute::ALL[index];
+        let growth = route.growth();
+
+        self.player.max_health += growth.hp;
+        self.player.health += growth.hp;
+        self.player.attack_power_bonus += growth.dmg;
+        self.player.bullet_count_bonus += growth.projectiles;
+        self.player.move_speed_bonus += growth.speed;
+        self.player.crit_rate += growth.crit;
+        if growth.atkspd > 0.0 {
+            for weapon in &mut self.player.weapons {
+                weapon.attack_speed *= 1.0 + growth.atkspd;
+            }
+        }
+
+        self.player.stat_allocation.bump(route);
+        self.player.stat_allocation.available_points -= 1;
+
+        if self.player.stat_allocation.available_points <= 0 {
+            self.complete_stat_allocation();
+        }
+    }
+
+    fn complete_stat_allocation(&mut self) {
+        self.player.level_up();
+        self.play_level_up_sound();
+
+        if self.player.experience >= self.player.experience_needed {
+            self.trigger_stat_allocation();
+        } else {
+            self.state = GameState::Battle;
+        }
+    }
+
     pub fn move_player(&mut self, dx: f32, dy: f32) {
         let new_x = (self.player.position.x + dx).clamp(25.0, self.screen_width - 25.0);
         let new_y = (self.player.position.y + dy).clamp(25.0, self.screen_height - 25.0);
@@ -1546,33 +2157,46 @@ impl Game {
     pub fn get_game_time(&self) -> f32 {
         self.start_time.elapsed().as_secs_f32()
     }
-    
-    pub fn select_weapon(&mut self, weapon_type: WeaponType) {
-        self.player.weapon = Weapon::new(weapon_type);
-        self.start_battle();
-    }
-    
-    pub fn login_attempt(&mut self) -> bool {
-        let username = self.user.username.clone();
-        let password = self.input_text.clone();
-        
-        match self.user.login(&self.pool, &username, &password) {
-            Ok(true) => {
-                self.state = GameState::MainMenu;
-                true
-            }
-            Ok(false) => {
-                println!("用户名或密码错误");
-                false
-            }
-            Err(e) => {
-                println!("数据库错误: {}", e);
-                false
-            }
+
+    /// 在`Battle`和`Paused`之间切换。恢复时把所有基于`Instant::elapsed()`计时的字段——
+    /// 战斗时钟`start_time`、刷怪计时器`last_spawn_time`、玩家的`last_damage_time`/
+    /// `last_shot_time`、每个敌人的`last_shot_time`、所有飘字的`spawn_time`，以及
+    /// 所有掉落道具的`spawn_time`——都前移暂停时长，这样战斗时钟、无敌帧、射击/刷怪冷却、
+    /// 飘字和道具的淡出进度在暂停期间都等效于时间没有流逝；漏掉任何一个都会让对应的计时器
+    /// 在暂停期间"偷跑"，恢复瞬间冷却已经跑完或道具已经淡出消失
+    pub fn toggle_pause(&mut self) {
+        match self.pause_started_at.take() {
+            Some(started) => {
+                let paused_for = started.elapsed();
+                self.start_time += paused_for;
+                self.last_spawn_time += paused_for;
+                self.player.last_damage_time += paused_for;
+                self.player.last_shot_time += paused_for;
+                for enemy in &mut self.enemies {
+                    enemy.last_shot_time += paused_for;
+                }
+                for floating_text in &mut self.floating_texts {
+                    floating_text.spawn_time += paused_for;
+                }
+                for item in &mut self.items {
+                    item.spawn_time += paused_for;
+                }
+                self.state = GameState::Battle;
+            },
+            None => {
+                self.pause_started_at = Some(Instant::now());
+                self.state = GameState::Paused;
+            },
         }
     }
 
-
+    pub fn select_weapon(&mut self, weapon_type: WeaponType) {
+        self.player.add_weapon(weapon_type.clone());
+        if let Some(idx) = self.player.weapons.iter().position(|w| w.weapon_type == weapon_type) {
+            self.player.current_weapon = idx;
+        }
+        self.start_battle();
+    }
     
     pub fn add_char_to_input(&mut self, ch: char) {
         if self.input_text.len() < 20 {
@@ -1616,6 +2240,20 @@ async fn load_game_texture(path: &str, name: &str) -> Option<Texture2D> {
     }
 }
 
+pub(crate) async fn load_game_sound(path: &str, name: &str) -> Option<Sound> {
+    println!("Loading {} sound...", name);
+    match macroquad::audio::load_sound(path).await {
+        Ok(sound) => {
+            println!("{} sound loaded successfully!", name);
+            Some(sound)
+        },
+        Err(e) => {
+            println!("Failed to load {} sound: {}", name, e);
+            None
+        }
+    }
+}
+
 #[macroquad::main(window_conf)]
 async fn main() {
    // ==================== 数据库初始化 ====================
@@ -1650,9 +2288,24 @@ async fn main() {
     
     // 加载道具纹理
     game.health_pack_texture = load_game_texture("resources/Health .png", "Health Pack").await;
-    
+
     println!("=== Texture Loading Complete ===");
-    
+
+    // 批量加载所有音效
+    println!("=== Loading Game Sounds ===");
+    game.audio.machinegun_fire = load_sound_pool(&["resources/sounds/machinegun_fire1.wav", "resources/sounds/machinegun_fire2.wav"], "MachineGun Fire").await;
+    game.audio.laser_fire = load_sound_pool(&["resources/sounds/laser_fire1.wav"], "Laser Fire").await;
+    game.audio.shotgun_fire = load_sound_pool(&["resources/sounds/shotgun_fire1.wav"], "Shotgun Fire").await;
+    game.audio.hit = load_sound_pool(&["resources/sounds/hit1.wav", "resources/sounds/hit2.wav"], "Hit").await;
+    game.audio.enemy_death = load_sound_pool(&["resources/sounds/enemy_death1.wav", "resources/sounds/enemy_death2.wav"], "Enemy Death").await;
+    game.audio.boss_death = load_sound_pool(&["resources/sounds/boss_death1.wav"], "Boss Death").await;
+    game.audio.item_pickup = load_sound_pool(&["resources/sounds/item_pickup1.wav"], "Item Pickup").await;
+    game.audio.level_up = load_sound_pool(&["resources/sounds/level_up1.wav"], "Level Up").await;
+    game.audio.rogue_select = load_sound_pool(&["resources/sounds/rogue_select1.wav"], "Rogue Select").await;
+    game.victory_sound = load_game_sound("resources/sounds/victory.wav", "Victory").await;
+    game.death_sound = load_game_sound("resources/sounds/death.wav", "Death").await;
+    println!("=== Sound Loading Complete ===");
+
     let mut last_time = get_time();
     
     loop {
@@ -1689,6 +2342,15 @@ fn handle_input_macroquad(game: &mut Game) {
                 game.state = GameState::Login;
                 game.input_mode = InputMode::Username;
                 game.clear_input();
+            } else if is_key_pressed(KeyCode::Key3) {
+                // 静音开关
+                game.master_volume = if game.master_volume > 0.0 { 0.0 } else { 1.0 };
+            } else if is_key_pressed(KeyCode::Key4) {
+                game.enter_leaderboard();
+            } else if is_key_pressed(KeyCode::Key5) {
+                game.state = GameState::Shop;
+            } else if is_key_pressed(KeyCode::Key6) {
+                game.use_stat_allocation = !game.use_stat_allocation;
             }
         },
         GameState::WeaponSelect => {
@@ -1706,7 +2368,7 @@ fn handle_input_macroquad(game: &mut Game) {
             handle_login_input(game);
         },
         GameState::Battle => {
-            let speed = 300.0;
+            let speed = 300.0 + game.player.move_speed_bonus;
             let dt = get_frame_time();
             
             // WASD移动
@@ -1722,11 +2384,36 @@ fn handle_input_macroquad(game: &mut Game) {
             if is_key_down(KeyCode::D) || is_key_down(KeyCode::Right) {
                 game.move_player(speed * dt, 0.0);
             }
-            
+
+            // Tab切换到库存中的下一把武器
+            if is_key_pressed(KeyCode::Tab) {
+                game.player.next_weapon();
+            }
+
             // ESC返回主菜单
             if is_key_pressed(KeyCode::Escape) {
                 game.state = GameState::MainMenu;
             }
+
+            // P键暂停
+            if is_key_pressed(KeyCode::P) {
+                game.toggle_pause();
+            }
+
+            // 空格键释放主动技能
+            if is_key_pressed(KeyCode::Space) {
+                game.try_activate_skill();
+            }
+        },
+        GameState::Paused => {
+            // P或回车键恢复战斗
+            if is_key_pressed(KeyCode::P) || is_key_pressed(KeyCode::Enter) {
+                game.toggle_pause();
+            } else if is_key_pressed(KeyCode::Escape) {
+                // 直接退回主菜单，不经过`toggle_pause`：本局作废，不需要再补偿暂停时长
+                game.pause_started_at = None;
+                game.state = GameState::MainMenu;
+            }
         },
         GameState::RogueSelection => {
             // 数字键选择肉鸽升级 - 更新为使用选项索引
@@ -1746,6 +2433,36 @@ fn handle_input_macroquad(game: &mut Game) {
                 game.state = GameState::MainMenu;
             }
         },
+        GameState::Leaderboard => {
+            if is_key_pressed(KeyCode::Escape) {
+                game.state = GameState::MainMenu;
+            }
+        },
+        GameState::Shop => {
+            if is_key_pressed(KeyCode::Key1) {
+                game.try_purchase_shop_upgrade(0);
+            } else if is_key_pressed(KeyCode::Key2) {
+                game.try_purchase_shop_upgrade(1);
+            } else if is_key_pressed(KeyCode::Key3) {
+                game.try_purchase_shop_upgrade(2);
+            } else if is_key_pressed(KeyCode::Key4) {
+                game.try_purchase_shop_upgrade(3);
+            } else if is_key_pressed(KeyCode::Escape) {
+                game.state = GameState::MainMenu;
+            }
+        },
+        GameState::StatAllocation => {
+            // 数字键把当前点数投入对应职业路线
+            if is_key_pressed(KeyCode::Key1) {
+                game.allocate_stat_point(0);
+            } else if is_key_pressed(KeyCode::Key2) {
+                game.allocate_stat_point(1);
+            } else if is_key_pressed(KeyCode::Key3) {
+                game.allocate_stat_point(2);
+            } else if is_key_pressed(KeyCode::Key4) {
+                game.allocate_stat_point(3);
+            }
+        },
     }
 }
 
@@ -1967,7 +2684,7 @@ fn render_game(game: &Game) {
     }
     
     // 绘制子弹
-    for bullet in &game.bullets {
+    for bullet in &game.bullet_manager.bullets {
         if bullet.is_player_bullet {
             // 玩家子弹 - 根据子弹类型选择纹理
             let (texture_opt, size) = match bullet.bullet_type {
@@ -2094,8 +2811,29 @@ fn render_game(game: &Game) {
                     draw_circle(item.position.x, item.position.y, 10.0, GREEN);
                 }
             }
+            ItemType::Dissipation => {
+                // 随存活时间线性淡出的消散标记
+                let fade = (1.0 - item.spawn_time.elapsed().as_secs_f32() / 0.4).max(0.0);
+                draw_circle(item.position.x, item.position.y, 6.0 * fade + 2.0, Color::new(1.0, 1.0, 1.0, fade * 0.6));
+            }
         }
     }
+
+    // 绘制浮空提示文字 - 命中/治疗/金币的即时反馈，随存活时间上漂并淡出
+    for floating_text in &game.floating_texts {
+        let age = floating_text.spawn_time.elapsed().as_secs_f32();
+        let alpha = (1.0 - age / FLOATING_TEXT_LIFETIME).max(0.0);
+        let faded_color = Color::new(floating_text.color.r, floating_text.color.g, floating_text.color.b, alpha);
+
+        let text_width = measure_text(&floating_text.text, None, floating_text.font_size as u16, 1.0).width;
+        draw_text(
+            &floating_text.text,
+            floating_text.position.x - text_width / 2.0,
+            floating_text.position.y,
+            floating_text.font_size,
+            faded_color,
+        );
+    }
 }
 
 // ==================== 渲染UI ====================
@@ -2138,9 +2876,15 @@ fn render_ui(game: &Game) {
             // 菜单选项
             draw_text("1. Start Game", center_x - 50.0, center_y - 40.0, font_size, WHITE);
             draw_text("2. Login", center_x - 50.0, center_y - 10.0, font_size, WHITE);
-            
+            let mute_label = if game.master_volume > 0.0 { "3. Mute Sound" } else { "3. Unmute Sound" };
+            draw_text(mute_label, center_x - 50.0, center_y + 20.0, font_size, WHITE);
+            draw_text("4. Leaderboard", center_x - 50.0, center_y + 50.0, font_size, WHITE);
+            draw_text(&format!("5. Shop ({} coins)", game.coins), center_x - 50.0, center_y + 80.0, font_size, WHITE);
+            let level_up_label = if game.use_stat_allocation { "6. Level-Up Mode: Stat Allocation" } else { "6. Level-Up Mode: Rogue Cards" };
+            draw_text(level_up_label, center_x - 50.0, center_y + 110.0, font_size, WHITE);
+
             // 操作提示
-            draw_text("Press 1-2 to select", center_x - 70.0, center_y + 80.0, 16.0, LIGHTGRAY);
+            draw_text("Press 1-6 to select", center_x - 70.0, center_y + 140.0, 16.0, LIGHTGRAY);
         },
         GameState::WeaponSelect => {
             // 标题
@@ -2209,7 +2953,11 @@ fn render_ui(game: &Game) {
         GameState::Battle => {
             // 玩家状态
             draw_text(&format!("HP: {}/{}", game.player.health, game.player.max_health), 10.0, 30.0, font_size, WHITE);
-            draw_text(&format!("LV: {}", game.player.level), 10.0, 55.0, font_size, WHITE);
+            let lv_text = match game.player.stat_allocation.current_title() {
+                Some((route_name, title)) => format!("LV: {} [{} {}]", game.player.level, route_name, title),
+                None => format!("LV: {}", game.player.level),
+            };
+            draw_text(&lv_text, 10.0, 55.0, font_size, WHITE);
             draw_text(&format!("EXP: {}/{}", game.player.experience, game.player.experience_needed), 10.0, 80.0, font_size, WHITE);
             
             // 本局统计（显示实时数据）
@@ -2221,25 +2969,45 @@ fn render_ui(game: &Game) {
                 let remaining_time = game.player.invincibility_duration - game.player.last_damage_time.elapsed().as_secs_f32();
                 draw_text(&format!("Invincible: {:.1}s", remaining_time), 10.0, 155.0, font_size, SKYBLUE);
             }
-            
+
+            // SP条和技能冷却
+            let sp_bar_width = 150.0;
+            let sp_bar_height = 12.0;
+            draw_rectangle(10.0, 180.0, sp_bar_width, sp_bar_height, DARKGRAY);
+            draw_rectangle(10.0, 180.0, sp_bar_width * (game.player.sp / game.player.max_sp), sp_bar_height, SKYBLUE);
+            draw_text(&format!("SP: {:.0}/{:.0}", game.player.sp, game.player.max_sp), 10.0 + sp_bar_width + 10.0, 190.0, font_size, SKYBLUE);
+
+            if let Some(skill) = &game.player.skill {
+                if skill.is_ready() {
+                    draw_text("Skill: READY (Space)", 10.0, 205.0, font_size, GREEN);
+                } else {
+                    draw_text(&format!("Skill: {:.1}s", skill.cooldown_timer), 10.0, 205.0, font_size, GRAY);
+                }
+            }
+
             // 游戏时间
             let time = game.get_game_time();
             let minutes = (time / 60.0) as i32;
             let seconds = (time % 60.0) as i32;
-            draw_text(&format!("Time: {}:{:02}", minutes, seconds), 10.0, 180.0, font_size, WHITE);
-            
+            draw_text(&format!("Time: {}:{:02}", minutes, seconds), 10.0, 230.0, font_size, WHITE);
+
             // 敌人和子弹数量
-            draw_text(&format!("Enemies: {}", game.enemies.len()), 10.0, 205.0, font_size, RED);
-            draw_text(&format!("Bullets: {}", game.bullets.len()), 10.0, 230.0, font_size, WHITE);
-            
+            draw_text(&format!("Enemies: {}", game.enemies.len()), 10.0, 255.0, font_size, RED);
+            draw_text(&format!("Bullets: {}", game.bullet_manager.bullets.len()), 10.0, 280.0, font_size, WHITE);
+
             // 武器信息
-            let weapon_name = match game.player.weapon.weapon_type {
+            let weapon_name = match game.player.weapon().weapon_type {
                 WeaponType::MachineGun => "Machinegun",
                 WeaponType::Laser => "Laser",
                 WeaponType::Shotgun => "Shotgun",
             };
-            draw_text(&format!("Weapon: {}", weapon_name), 10.0, 255.0, font_size, BLUE);
-            
+            let weapon_level = match game.player.weapon().level {
+                WeaponLevel::Level1 => 1,
+                WeaponLevel::Level2 => 2,
+                WeaponLevel::Level3 => 3,
+            };
+            draw_text(&format!("Weapon: {} Lv{}", weapon_name, weapon_level), 10.0, 305.0, font_size, BLUE);
+
             // Boss血条显示
             if let Some(boss) = game.enemies.iter().find(|e| e.enemy_type == EnemyType::Boss) {
                 let bar_width = 400.0;
@@ -2283,6 +3051,9 @@ fn render_ui(game: &Game) {
             // 使用新的卡片式界面
             render_rogue_selection_cards(game, center_x, center_y);
         },
+        GameState::Paused => {
+            render_paused(center_x, center_y);
+        },
         GameState::GameOver => {
             if let Some(result) = game.get_game_result() {
                 // 显示结算标题
@@ -2369,11 +3140,220 @@ fn render_ui(game: &Game) {
                 draw_text("ESC Return to main menu", center_x - 80.0, center_y + 90.0, 18.0, LIGHTGRAY);
             }
         },
+        GameState::Leaderboard => {
+            draw_text("Leaderboard", center_x - 70.0, center_y - 150.0, 32.0, YELLOW);
+
+            if game.leaderboard.is_empty() {
+                draw_text("No runs recorded yet", center_x - 100.0, center_y - 80.0, 18.0, LIGHTGRAY);
+            } else {
+                draw_text("RANK  PLAYER            TIME    LEVEL  RESULT", center_x - 160.0, center_y - 100.0, 16.0, LIGHTGRAY);
+
+                for (rank, entry) in game.leaderboard.iter().enumerate() {
+                    let minutes = (entry.survival_time / 60.0) as i32;
+                    let seconds = (entry.survival_time % 60.0) as i32;
+                    let result_label = if entry.victory { "WIN" } else { "LOSS" };
+                    let row = format!(
+                        "{:<6}{:<18}{:>2}m{:02}s  {:<7}{}",
+                        rank + 1,
+                        entry.username,
+                        minutes,
+                        seconds,
+                        entry.final_level,
+                        result_label
+                    );
+                    draw_text(&row, center_x - 160.0, center_y - 70.0 + rank as f32 * 22.0, 16.0, WHITE);
+                }
+            }
+
+            draw_text("ESC Return to main menu", center_x - 80.0, center_y + 180.0, 18.0, LIGHTGRAY);
+        },
+        GameState::Shop => {
+            render_shop(game, center_x, center_y);
+        },
+        GameState::StatAllocation => {
+            render_stat_allocation(game, center_x, center_y);
+        },
     }
 }
 
 // ==================== 渲染肉鸽升级卡片界面 ====================
 
+/// 战斗暂停界面：战斗画面已经由`render_game`画在底下了，这里只叠加半透明遮罩和菜单
+fn render_paused(center_x: f32, center_y: f32) {
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.6));
+
+    let title_text = "PAUSED";
+    let title_width = measure_text(title_text, None, 48, 1.0).width;
+    draw_text(title_text, center_x - title_width / 2.0, center_y - 40.0, 48.0, WHITE);
+
+    let resume_text = "P / Enter - Resume";
+    let resume_width = measure_text(resume_text, None, 22, 1.0).width;
+    draw_text(resume_text, center_x - resume_width / 2.0, center_y + 20.0, 22.0, LIGHTGRAY);
+
+    let quit_text = "ESC - Return to main menu";
+    let quit_width = measure_text(quit_text, None, 22, 1.0).width;
+    draw_text(quit_text, center_x - quit_width / 2.0, center_y + 55.0, 22.0, LIGHTGRAY);
+}
+
+/// 商店里四项永久强化的展示文案，顺序对应`ShopUpgrades::tier`/`SHOP_BASE_COSTS`的索引
+const SHOP_ITEMS: [(&str, &str, &str); 4] = [
+    ("Max HP", "+5 Max HP per tier", "♥"),
+    ("Base Damage", "+2 Attack Power per tier", "⚔"),
+    ("Move Speed", "+20 Move Speed per tier", "➤"),
+    ("Weapon Start EXP", "+50 starting weapon EXP per tier", "✦"),
+];
+
+/// 局外用金币买永久强化的商店界面，卡片网格复用`render_rogue_selection_cards`的布局参数
+fn render_shop(game: &Game, center_x: f32, center_y: f32) {
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.7));
+
+    let title_text = "Shop";
+    let title_width = measure_text(title_text, None, 28, 1.0).width;
+    draw_text(title_text, center_x - title_width / 2.0, center_y - 180.0, 28.0, Color::new(1.0, 0.9, 0.4, 1.0));
+
+    let coins_text = &format!("Coins: {}", game.coins);
+    let coins_width = measure_text(coins_text, None, 20, 1.0).width;
+    draw_text(coins_text, center_x - coins_width / 2.0, center_y - 140.0, 20.0, YELLOW);
+
+    let card_width = 180.0;
+    let card_height = 220.0;
+    let card_spacing = 20.0;
+    let total_width = SHOP_ITEMS.len() as f32 * card_width + (SHOP_ITEMS.len() - 1) as f32 * card_spacing;
+    let start_x = center_x - total_width / 2.0;
+
+    for (i, (name, desc, icon)) in SHOP_ITEMS.iter().enumerate() {
+        let card_x = start_x + i as f32 * (card_width + card_spacing);
+        let card_y = center_y - card_height / 2.0;
+
+        let tier = game.shop_upgrades.tier(i);
+        let cost = SHOP_BASE_COSTS[i] * (tier as i32 + 1);
+        let affordable = game.coins >= cost;
+
+        let (card_color, border_color) = if affordable {
+            (Color::new(0.2, 0.3, 0.4, 0.8), Color::new(0.3, 0.6, 1.0, 1.0))
+        } else {
+            (Color::new(0.2, 0.2, 0.2, 0.7), Color::new(0.4, 0.4, 0.4, 0.8))
+        };
+
+        draw_rectangle(card_x, card_y, card_width, card_height, card_color);
+        draw_rectangle_lines(card_x, card_y, card_width, card_height, 2.0, border_color);
+
+        let icon_size = 32.0;
+        let icon_width = measure_text(icon, None, icon_size as u16, 1.0).width;
+        draw_text(icon, card_x + (card_width - icon_width) / 2.0, card_y + 50.0, icon_size, WHITE);
+
+        let name_width = measure_text(name, None, 18, 1.0).width;
+        draw_text(name, card_x + (card_width - name_width) / 2.0, card_y + 85.0, 18.0, WHITE);
+
+        let tier_text = &format!("Tier {}", tier);
+        let tier_width = measure_text(tier_text, None, 16, 1.0).width;
+        draw_text(tier_text, card_x + (card_width - tier_width) / 2.0, card_y + 110.0, 16.0, Color::new(1.0, 0.9, 0.3, 1.0));
+
+        let desc_lines = layout_rich_text(desc, Color::new(0.9, 0.9, 0.9, 1.0), icon, 14, card_width - 16.0);
+        for (line_idx, runs) in desc_lines.iter().enumerate() {
+            let line_width: f32 = runs.iter().map(|(text, _)| measure_text(text, None, 14, 1.0).width).sum();
+            let mut run_x = card_x + (card_width - line_width) / 2.0;
+            let line_y = card_y + 140.0 + line_idx as f32 * 18.0;
+            for (text, color) in runs {
+                draw_text(text, run_x, line_y, 14.0, *color);
+                run_x += measure_text(text, None, 14, 1.0).width;
+            }
+        }
+
+        let cost_text = &format!("{} coins", cost);
+        let cost_color = if affordable { YELLOW } else { RED };
+        let cost_width = measure_text(cost_text, None, 16, 1.0).width;
+        draw_text(cost_text, card_x + (card_width - cost_width) / 2.0, card_y + card_height - 40.0, 16.0, cost_color);
+
+        if !affordable {
+            let warn_text = "Not enough coins";
+            let warn_width = measure_text(warn_text, None, 14, 1.0).width;
+            draw_text(warn_text, card_x + (card_width - warn_width) / 2.0, card_y + card_height - 20.0, 14.0, RED);
+        } else {
+            let hint_text = &format!("Press {}", i + 1);
+            let hint_width = measure_text(hint_text, None, 14, 1.0).width;
+            draw_text(hint_text, card_x + (card_width - hint_width) / 2.0, card_y + card_height - 20.0, 14.0, LIGHTGRAY);
+        }
+    }
+
+    let back_text = "ESC Return to main menu";
+    let back_width = measure_text(back_text, None, 18, 1.0).width;
+    draw_text(back_text, center_x - back_width / 2.0, center_y + 150.0, 18.0, Color::new(0.8, 0.8, 0.8, 1.0));
+}
+
+/// 手动升级的属性分配界面，卡片网格复用`render_rogue_selection_cards`的布局参数
+fn render_stat_allocation(game: &Game, center_x: f32, center_y: f32) {
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.7));
+
+    let title_text = "Allocate Stat Point";
+    let title_width = measure_text(title_text, None, 28, 1.0).width;
+    draw_text(title_text, center_x - title_width / 2.0, center_y - 180.0, 28.0, Color::new(1.0, 0.9, 0.4, 1.0));
+
+    let points_text = &format!("Points available: {}", game.player.stat_allocation.available_points);
+    let points_width = measure_text(points_text, None, 20, 1.0).width;
+    draw_text(points_text, center_x - points_width / 2.0, center_y - 140.0, 20.0, Color::new(0.8, 0.8, 0.8, 1.0));
+
+    let card_width = 180.0;
+    let card_height = 220.0;
+    let card_spacing = 20.0;
+    let routes = ClassRoute::ALL;
+    let total_width = routes.len() as f32 * card_width + (routes.len() - 1) as f32 * card_spacing;
+    let start_x = center_x - total_width / 2.0;
+
+    for (i, route) in routes.iter().enumerate() {
+        let card_x = start_x + i as f32 * (card_width + card_spacing);
+        let card_y = center_y - card_height / 2.0;
+
+        let points = game.player.stat_allocation.points(*route);
+        let unlocked = points >= route.title_threshold();
+        let (card_color, border_color) = if unlocked {
+            (Color::new(0.3, 0.6, 0.3, 0.9), Color::new(0.4, 1.0, 0.4, 1.0))
+        } else {
+            (Color::new(0.2, 0.3, 0.4, 0.8), Color::new(0.5, 0.5, 0.7, 1.0))
+        };
+
+        draw_rectangle(card_x, card_y, card_width, card_height, card_color);
+        draw_rectangle_lines(card_x, card_y, card_width, card_height, 2.0, border_color);
+
+        let icon = route.icon();
+        let icon_size = 32.0;
+        let icon_width = measure_text(icon, None, icon_size as u16, 1.0).width;
+        draw_text(icon, card_x + (card_width - icon_width) / 2.0, card_y + 50.0, icon_size, WHITE);
+
+        let name = route.name();
+        let name_width = measure_text(name, None, 18, 1.0).width;
+        draw_text(name, card_x + (card_width - name_width) / 2.0, card_y + 85.0, 18.0, WHITE);
+
+        let points_text = &format!("Invested: {}", points);
+        let points_line_width = measure_text(points_text, None, 16, 1.0).width;
+        draw_text(points_text, card_x + (card_width - points_line_width) / 2.0, card_y + 110.0, 16.0, Color::new(1.0, 0.9, 0.3, 1.0));
+
+        let growth = route.growth();
+        let mut stat_lines = Vec::new();
+        if growth.hp != 0 { stat_lines.push(format!("+{} HP", growth.hp)); }
+        if growth.dmg != 0 { stat_lines.push(format!("+{} DMG", growth.dmg)); }
+        if growth.atkspd > 0.0 { stat_lines.push(format!("+{:.0}% atk speed", growth.atkspd * 100.0)); }
+        if growth.projectiles != 0 { stat_lines.push(format!("+{} bullet", growth.projectiles)); }
+        if growth.speed > 0.0 { stat_lines.push(format!("+{:.0} move speed", growth.speed)); }
+        if growth.crit > 0.0 { stat_lines.push(format!("+{:.0}% crit", growth.crit * 100.0)); }
+
+        for (line_idx, line) in stat_lines.iter().enumerate() {
+            let line_width = measure_text(line, None, 14, 1.0).width;
+            draw_text(line, card_x + (card_width - line_width) / 2.0, card_y + 140.0 + line_idx as f32 * 18.0, 14.0, Color::new(0.9, 0.9, 0.9, 1.0));
+        }
+
+        if unlocked {
+            let title_text = route.title();
+            let title_width = measure_text(title_text, None, 14, 1.0).width;
+            draw_text(title_text, card_x + (card_width - title_width) / 2.0, card_y + card_height - 40.0, 14.0, Color::new(1.0, 1.0, 0.5, 1.0));
+        }
+
+        let hint_text = &format!("Press {}", i + 1);
+        let hint_width = measure_text(hint_text, None, 14, 1.0).width;
+        draw_text(hint_text, card_x + (card_width - hint_width) / 2.0, card_y + card_height - 20.0, 14.0, LIGHTGRAY);
+    }
+}
+
 fn render_rogue_selection_cards(game: &Game, center_x: f32, center_y: f32) {
     // 背景半透明遮罩
     draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.7));
@@ -2497,17 +3477,19 @@ fn render_rogue_selection_cards(game: &Game, center_x: f32, center_y: f32) {
             Color::new(1.0, 0.9, 0.3, 1.0) // 金黄色
         );
         
-        // 绘制详细描述（自动换行并居中）
-        let desc_lines = wrap_text(&upgrade.detailed_desc, 22); // 每行约22个字符
-        for (line_idx, line) in desc_lines.iter().enumerate() {
-            let line_width = measure_text(line, None, 14, 1.0).width;
-            draw_text(
-                line,
-                card_x + (card_width - line_width) / 2.0,
-                card_y + 140.0 + line_idx as f32 * 18.0,
-                14.0,
-                Color::new(0.9, 0.9, 0.9, 1.0) // 浅灰色
-            );
+        // 绘制详细描述（按卡片宽度换行并居中，支持`\c[r,g,b]`变色和`\i`插图标）
+        let desc_color = Color::new(0.9, 0.9, 0.9, 1.0); // 浅灰色
+        let desc_max_px = card_width - 16.0; // 左右各留8px边距
+        let desc_lines = layout_rich_text(&upgrade.detailed_desc, desc_color, &upgrade.icon, 14, desc_max_px);
+        for (line_idx, runs) in desc_lines.iter().enumerate() {
+            let line_width: f32 = runs.iter().map(|(text, _)| measure_text(text, None, 14, 1.0).width).sum();
+            let mut run_x = card_x + (card_width - line_width) / 2.0;
+            let line_y = card_y + 140.0 + line_idx as f32 * 18.0;
+
+            for (text, color) in runs {
+                draw_text(text, run_x, line_y, 14.0, *color);
+                run_x += measure_text(text, None, 14, 1.0).width;
+            }
         }
         
         // 绘制选择提示数字
@@ -2581,27 +3563,125 @@ fn render_rogue_selection_cards(game: &Game, center_x: f32, center_y: f32) {
     }
 }
 
-// ==================== 文本自动换行辅助函数 ====================
+// ==================== 富文本排版辅助函数 ====================
+
+/// 行内标记解析出的一段文字，`\c[r,g,b]`切换颜色、`\i`插入图标后，同色的连续文字归成一段
+struct RichAtom {
+    text: String,
+    color: Color,
+    /// 强制换行点，由原文里的`\n`产生
+    is_break: bool,
+}
+
+/// 从原始文本里摘出`\c[r,g,b]`（切换颜色）和`\i`（替换成`icon`）标记，
+/// 产出一串按顺序排列、每段带自己颜色的可见文字
+fn parse_inline_markup(text: &str, default_color: Color, icon: &str) -> Vec<RichAtom> {
+    let mut atoms = Vec::new();
+    let mut color = default_color;
+    let mut buf = String::new();
+    let mut chars = text.chars().peekable();
+
+    let flush = |buf: &mut String, color: Color, atoms: &mut Vec<RichAtom>| {
+        if !buf.is_empty() {
+            atoms.push(RichAtom { text: std::mem::take(buf), color, is_break: false });
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            flush(&mut buf, color, &mut atoms);
+            atoms.push(RichAtom { text: String::new(), color, is_break: true });
+        } else if c == '\\' && chars.peek() == Some(&'c') {
+            chars.next(); // 'c'
+            if chars.peek() == Some(&'[') {
+                chars.next(); // '['
+                let mut spec = String::new();
+                let mut closed = false;
+                while let Some(&nc) = chars.peek() {
+                    chars.next();
+                    if nc == ']' { closed = true; break; }
+                    spec.push(nc);
+                }
+                let parts: Vec<f32> = spec.split(',').filter_map(|p| p.trim().parse().ok()).collect();
+                if closed && parts.len() == 3 {
+                    flush(&mut buf, color, &mut atoms);
+                    color = Color::new(parts[0], parts[1], parts[2], 1.0);
+                } else {
+                    // 没闭合的`\c[`或颜色值不对：当普通文字原样保留，而不是丢掉剩余内容
+                    buf.push_str("\\c[");
+                    buf.push_str(&spec);
+                    if closed {
+                        buf.push(']');
+                    }
+                }
+            } else {
+                buf.push_str("\\c");
+            }
+        } else if c == '\\' && chars.peek() == Some(&'i') {
+            chars.next(); // 'i'
+            flush(&mut buf, color, &mut atoms);
+            atoms.push(RichAtom { text: icon.to_string(), color, is_break: false });
+        } else {
+            buf.push(c);
+        }
+    }
+    flush(&mut buf, color, &mut atoms);
+
+    atoms
+}
 
-fn wrap_text(text: &str, max_chars_per_line: usize) -> Vec<String> {
+/// 把带颜色的片段按`max_px`的可用宽度换行：拉丁文按空白分词整词换行，
+/// 没有空白的CJK之类脚本按单字换行；永远在`\n`处强制断行；
+/// 为避免死循环，单个token本身就超宽时也至少占满一行
+fn wrap_rich_atoms(atoms: Vec<RichAtom>, font_size: u16, max_px: f32) -> Vec<Vec<(String, Color)>> {
     let mut lines = Vec::new();
-    let mut current_line = String::new();
-    
-    for char in text.chars() {
-        current_line.push(char);
-        if current_line.len() >= max_chars_per_line || char == '\n' {
-            lines.push(current_line.clone());
-            current_line.clear();
+    let mut current_line: Vec<(String, Color)> = Vec::new();
+    let mut current_plain = String::new();
+
+    let mut push_token = |token: String, color: Color, lines: &mut Vec<Vec<(String, Color)>>, current_line: &mut Vec<(String, Color)>, current_plain: &mut String| {
+        let candidate = format!("{}{}", current_plain, token);
+        let width = measure_text(&candidate, None, font_size, 1.0).width;
+
+        if width > max_px && !current_line.is_empty() {
+            lines.push(std::mem::take(current_line));
+            current_plain.clear();
+        }
+
+        current_plain.push_str(&token);
+        current_line.push((token, color));
+    };
+
+    for atom in atoms {
+        if atom.is_break {
+            lines.push(std::mem::take(&mut current_line));
+            current_plain.clear();
+            continue;
+        }
+
+        for word in atom.text.split_inclusive(char::is_whitespace) {
+            if word.chars().all(|c| c.is_ascii()) {
+                push_token(word.to_string(), atom.color, &mut lines, &mut current_line, &mut current_plain);
+            } else {
+                for ch in word.chars() {
+                    push_token(ch.to_string(), atom.color, &mut lines, &mut current_line, &mut current_plain);
+                }
+            }
         }
     }
-    
+
     if !current_line.is_empty() {
         lines.push(current_line);
     }
-    
+
     lines
 }
 
+/// 解析行内标记并按像素宽度换行，拿到每行从左到右、带颜色的片段，供渲染时累加x偏移逐段绘制
+fn layout_rich_text(text: &str, default_color: Color, icon: &str, font_size: u16, max_px: f32) -> Vec<Vec<(String, Color)>> {
+    let atoms = parse_inline_markup(text, default_color, icon);
+    wrap_rich_atoms(atoms, font_size, max_px)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2617,14 +3697,16 @@ mod tests {
     #[test]
     fn test_weapon_enhancement() {
         let mut weapon = Weapon::new(WeaponType::MachineGun);
-        weapon.enhancement_level = 5;
-        assert_eq!(weapon.get_total_attack_power(), 7);
+        let result = weapon.add_experience(100);
+        assert_eq!(result, AddExperienceResult::LevelUp);
+        assert_eq!(weapon.level, WeaponLevel::Level2);
+        assert_eq!(weapon.get_total_attack_power(), 3);
     }
     
     #[test]
     fn test_enemy_damage() {
         let mut enemy = Enemy::new(EnemyType::Scout, Vec2::new(100.0, 100.0));
-        enemy.take_damage(10);
+        enemy.take_damage(10, Element::Neutral);
         assert_eq!(enemy.health, 10);
     }
     
@@ -2636,4 +3718,45 @@ mod tests {
         assert_eq!(player.experience, 0);
         assert_eq!(player.experience_needed, 200);
     }
+
+    #[test]
+    fn parse_inline_markup_switches_color_mid_word() {
+        let atoms = parse_inline_markup("ab\\c[1,0,0]cd", BLACK, "?");
+
+        assert_eq!(atoms.len(), 2);
+        assert_eq!(atoms[0].text, "ab");
+        assert_eq!(atoms[0].color, BLACK);
+        assert_eq!(atoms[1].text, "cd");
+        assert_eq!(atoms[1].color, Color::new(1.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn parse_inline_markup_keeps_unterminated_color_tag_as_plain_text() {
+        // 没有`]`收尾，或者方括号里的值凑不够3个分量：原样当普通文字保留，
+        // 不能把这一段连同后面的内容一起吞掉
+        let atoms = parse_inline_markup("ab\\c[1,2cd", BLACK, "?");
+
+        assert_eq!(atoms.len(), 1);
+        assert_eq!(atoms[0].text, "ab\\c[1,2cd");
+        assert_eq!(atoms[0].color, BLACK);
+    }
+
+    #[test]
+    fn wrap_rich_atoms_breaks_cjk_per_character_but_ascii_per_word() {
+        // 宽度设得极窄，使得任何非空文字一旦不是当前行的第一个token就必定换行，
+        // 这样不必依赖具体字体的像素测量结果，也能确定性地验证分词边界：
+        // 连续CJK没有空白可切，只能按单字拆；ASCII按空白整词切分，不会被从单词中间断开
+        let cjk_atoms = vec![RichAtom { text: "你好世界".to_string(), color: WHITE, is_break: false }];
+        let cjk_lines = wrap_rich_atoms(cjk_atoms, 16, 1.0);
+        assert_eq!(cjk_lines.len(), 4);
+        for line in &cjk_lines {
+            assert_eq!(line.len(), 1);
+        }
+
+        let ascii_atoms = vec![RichAtom { text: "hello world".to_string(), color: WHITE, is_break: false }];
+        let ascii_lines = wrap_rich_atoms(ascii_atoms, 16, 1.0);
+        assert_eq!(ascii_lines.len(), 2);
+        assert_eq!(ascii_lines[0][0].0, "hello ");
+        assert_eq!(ascii_lines[1][0].0, "world");
+    }
 }
\ No newline at end of file
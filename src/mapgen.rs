@@ -0,0 +1,200 @@
+use ::rand::prelude::*;
+
+// ==================== 地图生成（BSP地牢） ====================
+
+const MIN_ROOM_SIZE: i32 = 4;
+const MAX_SPLIT_DEPTH: i32 = 5;
+
+/// 地图格子类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Wall,
+    Floor,
+}
+
+/// 一个矩形房间（世界坐标，左上角+宽高）
+#[derive(Debug, Clone, Copy)]
+pub struct Room {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Room {
+    pub fn center(&self) -> (i32, i32) {
+        (self.x + self.w / 2, self.y + self.h / 2)
+    }
+}
+
+/// 生成好的地图：格子数组 + 房间列表
+pub struct Map {
+    pub width: i32,
+    pub height: i32,
+    pub tiles: Vec<Tile>,
+    pub rooms: Vec<Room>,
+}
+
+impl Map {
+    pub fn get(&self, x: i32, y: i32) -> Tile {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            Tile::Wall
+        } else {
+            self.tiles[(y * self.width + x) as usize]
+        }
+    }
+
+    fn set(&mut self, x: i32, y: i32, tile: Tile) {
+        if x >= 0 && y >= 0 && x < self.width && y < self.height {
+            self.tiles[(y * self.width + x) as usize] = tile;
+        }
+    }
+
+    pub fn is_opaque(&self, x: i32, y: i32) -> bool {
+        self.get(x, y) == Tile::Wall
+    }
+}
+
+struct BspNode {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    children: Option<(Box<BspNode>, Box<BspNode>)>,
+    room: Option<Room>,
+}
+
+impl BspNode {
+    fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
+        Self { x, y, w, h, children: None, room: None }
+    }
+
+    fn split(&mut self, depth: i32, rng: &mut impl Rng) {
+        if depth >= MAX_SPLIT_DEPTH {
+            return;
+        }
+
+        let split_horizontal = if self.w > self.h { false } else if self.h > self.w { true } else { rng.gen_bool(0.5) };
+
+        if split_horizontal {
+            let min_h = MIN_ROOM_SIZE + 1;
+            if self.h < min_h * 2 {
+                return;
+            }
+            let cut = rng.gen_range(min_h..=self.h - min_h);
+            let mut top = BspNode::new(self.x, self.y, self.w, cut);
+            let mut bottom = BspNode::new(self.x, self.y + cut, self.w, self.h - cut);
+            top.split(depth + 1, rng);
+            bottom.split(depth + 1, rng);
+            self.children = Some((Box::new(top), Box::new(bottom)));
+        } else {
+            let min_w = MIN_ROOM_SIZE + 1;
+            if self.w < min_w * 2 {
+                return;
+            }
+            let cut = rng.gen_range(min_w..=self.w - min_w);
+            let mut left = BspNode::new(self.x, self.y, cut, self.h);
+            let mut right = BspNode::new(self.x + cut, self.y, self.w - cut, self.h);
+            left.split(depth + 1, rng);
+            right.split(depth + 1, rng);
+            self.children = Some((Box::new(left), Box::new(right)));
+        }
+    }
+
+    fn carve_rooms(&mut self, map: &mut Map, rng: &mut impl Rng) {
+        match &mut self.children {
+            Some((left, right)) => {
+                left.carve_rooms(map, rng);
+                right.carve_rooms(map, rng);
+            }
+            None => {
+                let room_w = rng.gen_range(MIN_ROOM_SIZE..=self.w.max(MIN_ROOM_SIZE));
+                let room_h = rng.gen_range(MIN_ROOM_SIZE..=self.h.max(MIN_ROOM_SIZE));
+                let room_x = self.x + rng.gen_range(0..=(self.w - room_w).max(0));
+                let room_y = self.y + rng.gen_range(0..=(self.h - room_h).max(0));
+
+                let room = Room { x: room_x, y: room_y, w: room_w, h: room_h };
+                for y in room.y..room.y + room.h {
+                    for x in room.x..room.x + room.w {
+                        map.set(x, y, Tile::Floor);
+                    }
+                }
+                self.room = Some(room);
+                map.rooms.push(room);
+            }
+        }
+    }
+
+    /// 按树形结构用L形走廊连接兄弟房间，返回一个代表子树的房间中心点
+    fn connect(&self, map: &mut Map, rng: &mut impl Rng) -> Option<(i32, i32)> {
+        match &self.children {
+            Some((left, right)) => {
+                let left_center = left.connect(map, rng);
+                let right_center = right.connect(map, rng);
+                if let (Some(a), Some(b)) = (left_center, right_center) {
+                    carve_l_corridor(map, a, b, rng);
+                }
+                left_center.or(right_center)
+            }
+            None => self.room.map(|r| r.center()),
+        }
+    }
+}
+
+fn carve_l_corridor(map: &mut Map, a: (i32, i32), b: (i32, i32), rng: &mut impl Rng) {
+    let (ax, ay) = a;
+    let (bx, by) = b;
+
+    if rng.gen_bool(0.5) {
+        carve_h_line(map, ax, bx, ay);
+        carve_v_line(map, ay, by, bx);
+    } else {
+        carve_v_line(map, ay, by, ax);
+        carve_h_line(map, ax, bx, by);
+    }
+}
+
+fn carve_h_line(map: &mut Map, x1: i32, x2: i32, y: i32) {
+    for x in x1.min(x2)..=x1.max(x2) {
+        map.set(x, y, Tile::Floor);
+    }
+}
+
+fn carve_v_line(map: &mut Map, y1: i32, y2: i32, x: i32) {
+    for y in y1.min(y2)..=y1.max(y2) {
+        map.set(x, y, Tile::Floor);
+    }
+}
+
+/// 使用BSP算法生成一张地牢地图，给定`seed`可复现
+pub fn generate_map(width: i32, height: i32, seed: u64) -> Map {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut map = Map {
+        width,
+        height,
+        tiles: vec![Tile::Wall; (width * height) as usize],
+        rooms: Vec::new(),
+    };
+
+    let mut root = BspNode::new(0, 0, width, height);
+    root.split(0, &mut rng);
+    root.carve_rooms(&mut map, &mut rng);
+    root.connect(&mut map, &mut rng);
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_map_has_floors_and_is_reproducible() {
+        let map_a = generate_map(40, 30, 42);
+        let map_b = generate_map(40, 30, 42);
+
+        assert!(!map_a.rooms.is_empty());
+        assert!(map_a.tiles.iter().any(|&t| t == Tile::Floor));
+        assert_eq!(map_a.tiles, map_b.tiles);
+    }
+}
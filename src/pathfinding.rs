@@ -0,0 +1,125 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+// ==================== A*寻路 ====================
+
+const SQRT2_MINUS_1: f32 = std::f32::consts::SQRT_2 - 1.0;
+
+/// 8方向移动的octile启发函数
+fn octile_heuristic(a: (i32, i32), b: (i32, i32)) -> f32 {
+    let dx = (a.0 - b.0).abs() as f32;
+    let dy = (a.1 - b.1).abs() as f32;
+    dx.max(dy) + SQRT2_MINUS_1 * dx.min(dy)
+}
+
+#[derive(Copy, Clone, PartialEq)]
+struct OpenEntry {
+    f: f32,
+    node: (i32, i32),
+}
+
+impl Eq for OpenEntry {}
+
+impl Ord for OpenEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // 二叉堆是大顶堆，这里取反使f越小优先级越高
+        other.f.partial_cmp(&self.f).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for OpenEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [
+    (1, 0), (-1, 0), (0, 1), (0, -1),
+    (1, 1), (1, -1), (-1, 1), (-1, -1),
+];
+
+/// 在8方向格子图上寻路；`is_blocked`返回true代表该格不可通过
+pub fn astar(
+    start: (i32, i32),
+    goal: (i32, i32),
+    is_blocked: impl Fn(i32, i32) -> bool,
+) -> Option<Vec<(i32, i32)>> {
+    if is_blocked(goal.0, goal.1) {
+        return None;
+    }
+
+    let mut open_set = BinaryHeap::new();
+    let mut came_from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+    let mut g_score: HashMap<(i32, i32), f32> = HashMap::new();
+
+    g_score.insert(start, 0.0);
+    open_set.push(OpenEntry { f: octile_heuristic(start, goal), node: start });
+
+    let mut visited = std::collections::HashSet::new();
+
+    while let Some(OpenEntry { node: current, .. }) = open_set.pop() {
+        if current == goal {
+            return Some(reconstruct_path(&came_from, current));
+        }
+
+        if !visited.insert(current) {
+            continue;
+        }
+
+        let current_g = g_score[&current];
+
+        for (dx, dy) in NEIGHBOR_OFFSETS {
+            let neighbor = (current.0 + dx, current.1 + dy);
+            if is_blocked(neighbor.0, neighbor.1) {
+                continue;
+            }
+
+            let step_cost = if dx != 0 && dy != 0 { std::f32::consts::SQRT_2 } else { 1.0 };
+            let tentative_g = current_g + step_cost;
+
+            if tentative_g < *g_score.get(&neighbor).unwrap_or(&f32::INFINITY) {
+                came_from.insert(neighbor, current);
+                g_score.insert(neighbor, tentative_g);
+                let f = tentative_g + octile_heuristic(neighbor, goal);
+                open_set.push(OpenEntry { f, node: neighbor });
+            }
+        }
+    }
+
+    None
+}
+
+fn reconstruct_path(came_from: &HashMap<(i32, i32), (i32, i32)>, mut current: (i32, i32)) -> Vec<(i32, i32)> {
+    let mut path = vec![current];
+    while let Some(&prev) = came_from.get(&current) {
+        current = prev;
+        path.push(current);
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_straight_path_on_open_grid() {
+        let path = astar((0, 0), (3, 0), |_, _| false).unwrap();
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(3, 0)));
+    }
+
+    #[test]
+    fn routes_around_a_wall() {
+        let is_blocked = |x: i32, y: i32| y == 1 && (0..5).contains(&x);
+        let path = astar((2, 0), (2, 2), is_blocked).unwrap();
+        assert!(path.iter().all(|&(x, y)| !is_blocked(x, y)));
+    }
+
+    #[test]
+    fn returns_none_when_goal_unreachable() {
+        let is_blocked = |x: i32, _y: i32| x == 1;
+        assert!(astar((0, 0), (5, 0), is_blocked).is_none());
+    }
+}
@@ -0,0 +1,246 @@
+use crate::*;
+use ::rand::prelude::*;
+use mysql::*;
+use mysql::prelude::*;
+
+// ==================== 游戏结算系统 ====================
+
+/// 游戏结算结构
+#[derive(Debug, Clone)]
+pub struct GameResult {
+    pub victory: bool,
+    pub final_level: i32,
+    pub coins_earned: i32,
+    pub experience_gained: i32,
+    pub survival_time: f32,
+    pub enemies_defeated: i32,
+    pub total_damage_dealt: i32,
+    pub weapon_used: WeaponType,
+}
+
+impl GameResult {
+    pub fn new(player: &Player, victory: bool, time: f32, enemies_defeated: i32, total_damage: i32) -> Self {
+        Self {
+            victory,
+            final_level: player.level,
+            coins_earned: 0,
+            experience_gained: 0,
+            survival_time: time,
+            enemies_defeated,
+            total_damage_dealt: total_damage,
+            weapon_used: player.weapon().weapon_type.clone(),
+        }
+    }
+
+    // 将本局战绩写入runs表，并累计到user_stats
+    pub fn save(&self, pool: &Pool, username: &str) -> Result<()> {
+        let mut conn = pool.get_conn()?;
+
+        conn.exec_drop(
+            "INSERT INTO runs (username, survival_time, final_level, coins_earned, enemies_defeated, total_damage_dealt, weapon_used, victory) \
+             VALUES (:username, :survival_time, :final_level, :coins_earned, :enemies_defeated, :total_damage_dealt, :weapon_used, :victory)",
+            params! {
+                "username" => username,
+                "survival_time" => self.survival_time,
+                "final_level" => self.final_level,
+                "coins_earned" => self.coins_earned,
+                "enemies_defeated" => self.enemies_defeated,
+                "total_damage_dealt" => self.total_damage_dealt,
+                "weapon_used" => format!("{:?}", self.weapon_used),
+                "victory" => self.victory,
+            },
+        )?;
+
+        // 累计到user_stats：已有记录则叠加，否则插入新行
+        conn.exec_drop(
+            "INSERT INTO user_stats (username, total_coins, best_survival_time, total_wins) \
+             VALUES (:username, :coins, :survival_time, :win) \
+             ON DUPLICATE KEY UPDATE \
+                total_coins = total_coins + :coins, \
+                best_survival_time = GREATEST(best_survival_time, :survival_time), \
+                total_wins = total_wins + :win",
+            params! {
+                "username" => username,
+                "coins" => self.coins_earned,
+                "survival_time" => self.survival_time,
+                "win" => if self.victory { 1 } else { 0 },
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+/// 排行榜里的一行战绩
+#[derive(Debug, Clone)]
+pub struct LeaderboardEntry {
+    pub username: String,
+    pub survival_time: f32,
+    pub final_level: i32,
+    pub victory: bool,
+}
+
+// ==================== 用户系统 ====================
+
+/// 用户数据
+#[derive(Debug, Clone)]
+pub struct User {
+    pub username: String,
+    pub password: String,
+    pub is_logged_in: bool,
+}
+
+/// 生成一段随机盐值，用于密码加盐哈希
+fn generate_salt() -> String {
+    let mut rng = ::rand::thread_rng();
+    format!("{:016x}", rng.gen::<u64>())
+}
+
+/// 用盐对密码做哈希。这里必须用Argon2这类内存困难的密码KDF，而不是`DefaultHasher`（SipHash，
+/// 为哈希表设计，没有工作量因子）——否则`users`表一旦泄露，离线暴力破解的成本几乎为零
+fn hash_password(password: &str, salt: &str) -> String {
+    use argon2::Argon2;
+
+    let mut output = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt.as_bytes(), &mut output)
+        .expect("固定长度的输出缓冲区和非空盐值不会触发Argon2的参数校验失败");
+    output.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl User {
+    pub fn new() -> Self {
+        Self {
+            username: String::new(),
+            password: String::new(),
+            is_logged_in: false,
+        }
+    }
+
+    // 传入 MySQL 连接池和用户输入，验证登录
+    pub fn login(&mut self, pool: &Pool, username: &str, password: &str) -> Result<bool> {
+        let mut conn = pool.get_conn()?;
+
+        // 查询数据库，验证用户名密码哈希是否匹配
+        let result: Option<(String, String)> = conn.exec_first(
+            "SELECT password_hash, salt FROM users WHERE username = :username",
+            params! {
+                "username" => username,
+            },
+        )?;
+
+        if let Some((stored_hash, salt)) = result {
+            if hash_password(password, &salt) == stored_hash {
+                self.username = username.to_string();
+                self.password = password.to_string();
+                self.is_logged_in = true;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    // 新建账号：用户名已存在时返回Ok(false)，否则写入盐值和密码哈希
+    pub fn register(pool: &Pool, username: &str, password: &str) -> Result<bool> {
+        let mut conn = pool.get_conn()?;
+
+        let existing: Option<String> = conn.exec_first(
+            "SELECT username FROM users WHERE username = :username",
+            params! {
+                "username" => username,
+            },
+        )?;
+
+        if existing.is_some() {
+            return Ok(false);
+        }
+
+        let salt = generate_salt();
+        let password_hash = hash_password(password, &salt);
+
+        conn.exec_drop(
+            "INSERT INTO users (username, password_hash, salt) VALUES (:username, :password_hash, :salt)",
+            params! {
+                "username" => username,
+                "password_hash" => password_hash,
+                "salt" => salt,
+            },
+        )?;
+
+        Ok(true)
+    }
+}
+
+impl Game {
+    /// 按生存时间取前`limit`名战绩，拼成排行榜
+    fn load_leaderboard(&self, limit: u32) -> Result<Vec<LeaderboardEntry>> {
+        let mut conn = self.pool.get_conn()?;
+
+        let rows: Vec<(String, f32, i32, bool)> = conn.exec(
+            "SELECT username, survival_time, final_level, victory FROM runs ORDER BY survival_time DESC LIMIT :limit",
+            params! {
+                "limit" => limit,
+            },
+        )?;
+
+        Ok(rows.into_iter().map(|(username, survival_time, final_level, victory)| {
+            LeaderboardEntry { username, survival_time, final_level, victory }
+        }).collect())
+    }
+
+    /// 切到排行榜界面并立即拉取一次最新数据
+    pub fn enter_leaderboard(&mut self) {
+        self.state = GameState::Leaderboard;
+
+        match self.load_leaderboard(10) {
+            Ok(entries) => self.leaderboard = entries,
+            Err(e) => println!("排行榜加载失败: {}", e),
+        }
+    }
+
+    pub fn login_attempt(&mut self) -> bool {
+        let username = self.user.username.clone();
+        let password = self.input_text.clone();
+
+        match self.user.login(&self.pool, &username, &password) {
+            Ok(true) => {
+                self.state = GameState::MainMenu;
+                true
+            }
+            Ok(false) => {
+                println!("用户名或密码错误");
+                false
+            }
+            Err(e) => {
+                println!("数据库错误: {}", e);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `User::login`/`User::register`需要一个真实的`mysql::Pool`，这个仓库里没有DB mock层，
+    // 所以这里只覆盖`hash_password`本身这部分纯逻辑：同样的密码+盐必须总是得到同样的哈希，
+    // 换掉盐或密码中任意一个都必须让哈希改变——这正是`register`里"先查用户名是否存在，
+    // 再用不同的盐哈希密码"这套唯一性检查能够成立的前提。
+
+    #[test]
+    fn same_password_and_salt_hash_deterministically() {
+        assert_eq!(hash_password("hunter2", "abc12345"), hash_password("hunter2", "abc12345"));
+    }
+
+    #[test]
+    fn different_passwords_hash_differently_under_the_same_salt() {
+        assert_ne!(hash_password("hunter2", "abc12345"), hash_password("hunter3", "abc12345"));
+    }
+
+    #[test]
+    fn different_salts_hash_the_same_password_differently() {
+        assert_ne!(hash_password("hunter2", "abc12345"), hash_password("hunter2", "xyz78901"));
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+use macroquad::prelude::*;
+
+// ==================== 图块集渲染 ====================
+
+/// 一张方格图块集，按`tile_size`切分出若干命名的图块
+pub struct TileAtlas {
+    pub texture: Texture2D,
+    pub tile_size: f32,
+    glyphs: HashMap<&'static str, (u32, u32)>,
+}
+
+impl TileAtlas {
+    pub async fn load(path: &str, tile_size: f32) -> Option<Self> {
+        let texture = load_texture(path).await.ok()?;
+        texture.set_filter(FilterMode::Nearest);
+
+        let mut glyphs = HashMap::new();
+        // 图集中各命名图块的(列, 行)坐标，对应urizen_onebit风格的布局
+        glyphs.insert("player", (0, 0));
+        glyphs.insert("wall", (1, 0));
+        glyphs.insert("floor", (2, 0));
+        glyphs.insert("enemy_scout", (0, 1));
+        glyphs.insert("enemy_heavy", (1, 1));
+
+        Some(Self { texture, tile_size, glyphs })
+    }
+
+    fn source_rect(&self, col: u32, row: u32) -> Rect {
+        Rect::new(col as f32 * self.tile_size, row as f32 * self.tile_size, self.tile_size, self.tile_size)
+    }
+
+    /// 以屏幕/世界像素坐标`(x, y)`为左上角绘制一个命名图块（网格坐标需由调用方先乘以图块像素尺寸），
+    /// `tint`用于战争迷雾变暗等效果
+    pub fn draw_tile(&self, glyph: &str, x: f32, y: f32, dest_size: f32, tint: Color) {
+        let (col, row) = *self.glyphs.get(glyph).unwrap_or(&(0, 0));
+        draw_texture_ex(
+            &self.texture,
+            x,
+            y,
+            tint,
+            DrawTextureParams {
+                dest_size: Some(macroquad::math::Vec2::new(dest_size, dest_size)),
+                source: Some(self.source_rect(col, row)),
+                ..Default::default()
+            },
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use crate::*;
+
+// ==================== 商店系统 ====================
+
+/// 商店里四项永久强化各自的基础售价，实际售价是`base_cost * (tier + 1)`
+const SHOP_BASE_COSTS: [i32; 4] = [50, 60, 70, 80];
+
+/// 局外用金币买的永久强化，按等阶累加；每开始新的一局都会重新套用到玩家身上
+#[derive(Debug, Clone)]
+pub struct ShopUpgrades {
+    pub max_hp_tier: u32,
+    pub damage_tier: u32,
+    pub speed_tier: u32,
+    pub weapon_exp_tier: u32,
+}
+
+impl ShopUpgrades {
+    pub fn new() -> Self {
+        Self { max_hp_tier: 0, damage_tier: 0, speed_tier: 0, weapon_exp_tier: 0 }
+    }
+
+    /// 按索引取某一项当前的等阶，索引顺序对应商店卡片的显示顺序
+    pub fn tier(&self, index: usize) -> u32 {
+        match index {
+            0 => self.max_hp_tier,
+            1 => self.damage_tier,
+            2 => self.speed_tier,
+            3 => self.weapon_exp_tier,
+            _ => 0,
+        }
+    }
+
+    fn bump_tier(&mut self, index: usize) {
+        match index {
+            0 => self.max_hp_tier += 1,
+            1 => self.damage_tier += 1,
+            2 => self.speed_tier += 1,
+            3 => self.weapon_exp_tier += 1,
+            _ => {},
+        }
+    }
+
+    /// 把当前等阶的加成套用到新的一局玩家身上
+    pub fn apply_to(&self, player: &mut Player) {
+        let hp_bonus = self.max_hp_tier as i32 * 5;
+        player.max_health += hp_bonus;
+        player.health += hp_bonus;
+
+        player.attack_power_bonus += self.damage_tier as i32 * 2;
+        player.move_speed_bonus += self.speed_tier as f32 * 20.0;
+
+        if self.weapon_exp_tier > 0 {
+            player.weapon_mut().add_experience(self.weapon_exp_tier as u16 * 50);
+        }
+    }
+}
+
+/// 手动升级的职业路线：每条路线每点投入带来一组固定的属性增量
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClassRoute {
+    Warrior,
+    Knight,
+    Mage,
+    Rogue,
+}
+
+/// 某条职业路线每投入一点带来的固定属性增量
+pub struct RouteGrowth {
+    pub hp: i32,
+    pub dmg: i32,
+    pub atkspd: f32,
+    pub projectiles: i32,
+    pub speed: f32,
+    pub crit: f32,
+}
+
+impl ClassRoute {
+    pub const ALL: [ClassRoute; 4] = [ClassRoute::Warrior, ClassRoute::Knight, ClassRoute::Mage, ClassRoute::Rogue];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClassRoute::Warrior => "Warrior",
+            ClassRoute::Knight => "Knight",
+            ClassRoute::Mage => "Mage",
+            ClassRoute::Rogue => "Rogue",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            ClassRoute::Warrior => "⚔",
+            ClassRoute::Knight => "◊",
+            ClassRoute::Mage => "✦",
+            ClassRoute::Rogue => "➹",
+        }
+    }
+
+    /// Warrior偏血量与伤害，Knight各项均衡，Mage偏攻速与弹幕，Rogue偏移速与暴击
+    pub fn growth(&self) -> RouteGrowth {
+        match self {
+            ClassRoute::Warrior => RouteGrowth { hp: 6, dmg: 5, atkspd: 0.0, projectiles: 0, speed: 2.0, crit: 0.0 },
+            ClassRoute::Knight => RouteGrowth { hp: 3, dmg: 2, atkspd: 0.02, projectiles: 0, speed: 1.0, crit: 0.01 },
+            ClassRoute::Mage => RouteGrowth { hp: 0, dmg: 0, atkspd: 0.05, projectiles: 1, speed: 0.0, crit: 0.0 },
+            ClassRoute::Rogue => RouteGrowth { hp: 0, dmg: 0, atkspd: 0.0, projectiles: 0, speed: 3.0, crit: 0.03 },
+        }
+    }
+
+    /// 投入点数达到此门槛即解锁该路线的称号
+    pub fn title_threshold(&self) -> u32 {
+        5
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ClassRoute::Warrior => "Berserker",
+            ClassRoute::Knight => "Guardian",
+            ClassRoute::Mage => "Archmage",
+            ClassRoute::Rogue => "Assassin",
+        }
+    }
+}
+
+/// 玩家在各条职业路线上的投入进度，随角色一起逐局重置
+#[derive(Debug, Clone)]
+pub struct StatAllocation {
+    pub available_points: i32,
+    pub warrior_points: u32,
+    pub knight_points: u32,
+    pub mage_points: u32,
+    pub rogue_points: u32,
+}
+
+impl StatAllocation {
+    pub fn new() -> Self {
+        Self { available_points: 0, warrior_points: 0, knight_points: 0, mage_points: 0, rogue_points: 0 }
+    }
+
+    pub fn points(&self, route: ClassRoute) -> u32 {
+        match route {
+            ClassRoute::Warrior => self.warrior_points,
+            ClassRoute::Knight => self.knight_points,
+            ClassRoute::Mage => self.mage_points,
+            ClassRoute::Rogue => self.rogue_points,
+        }
+    }
+
+    pub fn bump(&mut self, route: ClassRoute) {
+        match route {
+            ClassRoute::Warrior => self.warrior_points += 1,
+            ClassRoute::Knight => self.knight_points += 1,
+            ClassRoute::Mage => self.mage_points += 1,
+            ClassRoute::Rogue => self.rogue_points += 1,
+        }
+    }
+
+    /// 投入最多且已跨过称号门槛的路线，用于战斗HUD展示；平局时取`ClassRoute::ALL`中靠前的
+    pub fn current_title(&self) -> Option<(&'static str, &'static str)> {
+        ClassRoute::ALL.iter()
+            .rev()
+            .map(|route| (*route, self.points(*route)))
+            .filter(|(route, points)| *points >= route.title_threshold())
+            .max_by_key(|(_, points)| *points)
+            .map(|(route, _)| (route.name(), route.title()))
+    }
+}
+
+impl Game {
+    /// 按商店卡片索引购买一级永久强化：金币不够就什么都不做
+    pub fn try_purchase_shop_upgrade(&mut self, index: usize) {
+        if index >= SHOP_BASE_COSTS.len() {
+            return;
+        }
+
+        let cost = SHOP_BASE_COSTS[index] * (self.shop_upgrades.tier(index) as i32 + 1);
+        if self.coins < cost {
+            return;
+        }
+
+        self.coins -= cost;
+        self.shop_upgrades.bump_tier(index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bump_tier_increments_the_matching_upgrade_and_leaves_the_rest() {
+        let mut upgrades = ShopUpgrades::new();
+        upgrades.bump_tier(1);
+
+        assert_eq!(upgrades.tier(0), 0);
+        assert_eq!(upgrades.tier(1), 1);
+        assert_eq!(upgrades.tier(2), 0);
+    }
+
+    #[test]
+    fn cost_formula_scales_with_current_tier() {
+        let mut upgrades = ShopUpgrades::new();
+        assert_eq!(SHOP_BASE_COSTS[0] * (upgrades.tier(0) as i32 + 1), 50);
+
+        upgrades.bump_tier(0);
+        assert_eq!(SHOP_BASE_COSTS[0] * (upgrades.tier(0) as i32 + 1), 100);
+
+        upgrades.bump_tier(0);
+        assert_eq!(SHOP_BASE_COSTS[0] * (upgrades.tier(0) as i32 + 1), 150);
+    }
+
+    #[test]
+    fn current_title_is_none_below_the_threshold() {
+        let mut allocation = StatAllocation::new();
+        for _ in 0..4 {
+            allocation.bump(ClassRoute::Warrior);
+        }
+
+        assert_eq!(allocation.current_title(), None);
+    }
+
+    #[test]
+    fn current_title_unlocks_once_a_route_crosses_the_threshold() {
+        let mut allocation = StatAllocation::new();
+        for _ in 0..5 {
+            allocation.bump(ClassRoute::Mage);
+        }
+
+        assert_eq!(allocation.current_title(), Some(("Mage", "Archmage")));
+    }
+
+    #[test]
+    fn tied_points_favor_the_route_earlier_in_class_route_all() {
+        let mut allocation = StatAllocation::new();
+        for _ in 0..5 {
+            allocation.bump(ClassRoute::Warrior);
+            allocation.bump(ClassRoute::Rogue);
+        }
+
+        assert_eq!(allocation.current_title(), Some(("Warrior", "Berserker")));
+    }
+}
@@ -0,0 +1,90 @@
+use crate::*;
+
+// ==================== 增益/减益效果系统 ====================
+
+/// 灼烧效果的固定id，同一目标身上的灼烧效果以此叠加层数
+pub const STATUS_ID_BURNING: u32 = 1;
+/// 冰冻效果的固定id，重复命中只刷新持续时间，不会无限叠加减速
+pub const STATUS_ID_FREEZE: u32 = 2;
+/// 眩晕效果的固定id
+pub const STATUS_ID_STUN: u32 = 3;
+/// 过载射击（主动技能）的固定id
+pub const STATUS_ID_OVERDRIVE: u32 = 4;
+
+/// 状态效果的具体种类
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EffectKind {
+    /// 每层每次跳动造成的灼烧伤害
+    Burning(i32),
+    /// 攻速加成倍率（乘到开火间隔的倒数上，过载射击复用这个种类）
+    AttackBoost(f32),
+    /// 移动/攻击速度减速系数（0.5表示减速50%），冰冻效果复用这个种类
+    Slow(f32),
+    /// 护盾值
+    Shield(i32),
+    /// 眩晕：移动和开火全部冻结
+    Stun,
+}
+
+/// 一个可叠加的状态效果（增益或减益）
+#[derive(Debug, Clone)]
+pub struct StatusEffect {
+    pub id: u32,
+    pub remaining: f32,
+    pub tick_interval: f32,
+    pub time_since_tick: f32,
+    pub stacks: u32,
+    pub kind: EffectKind,
+}
+
+impl StatusEffect {
+    pub fn new(id: u32, remaining: f32, tick_interval: f32, kind: EffectKind) -> Self {
+        Self { id, remaining, tick_interval, time_since_tick: 0.0, stacks: 1, kind }
+    }
+}
+
+/// 将新效果并入列表：同`id`的效果叠加层数并刷新持续时间，否则追加新效果
+pub fn apply_status_effect(effects: &mut Vec<StatusEffect>, new_effect: StatusEffect) {
+    if let Some(existing) = effects.iter_mut().find(|e| e.id == new_effect.id) {
+        existing.stacks += 1;
+        existing.remaining = existing.remaining.max(new_effect.remaining);
+    } else {
+        effects.push(new_effect);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_effect_is_appended_when_no_matching_id_exists() {
+        let mut effects = Vec::new();
+        apply_status_effect(&mut effects, StatusEffect::new(STATUS_ID_BURNING, 3.0, 1.0, EffectKind::Burning(5)));
+
+        assert_eq!(effects.len(), 1);
+        assert_eq!(effects[0].stacks, 1);
+    }
+
+    #[test]
+    fn matching_id_stacks_instead_of_appending() {
+        let mut effects = Vec::new();
+        apply_status_effect(&mut effects, StatusEffect::new(STATUS_ID_BURNING, 3.0, 1.0, EffectKind::Burning(5)));
+        apply_status_effect(&mut effects, StatusEffect::new(STATUS_ID_BURNING, 2.0, 1.0, EffectKind::Burning(5)));
+
+        assert_eq!(effects.len(), 1, "同id的效果应该叠加层数而不是追加一个新实例");
+        assert_eq!(effects[0].stacks, 2);
+    }
+
+    #[test]
+    fn stacking_refreshes_remaining_to_the_longer_duration() {
+        let mut effects = Vec::new();
+        apply_status_effect(&mut effects, StatusEffect::new(STATUS_ID_FREEZE, 1.0, 1.0, EffectKind::Slow(0.5)));
+        apply_status_effect(&mut effects, StatusEffect::new(STATUS_ID_FREEZE, 4.0, 1.0, EffectKind::Slow(0.5)));
+
+        assert_eq!(effects[0].remaining, 4.0, "刷新应该取两者中较长的剩余时间，而不是直接覆盖");
+
+        apply_status_effect(&mut effects, StatusEffect::new(STATUS_ID_FREEZE, 0.5, 1.0, EffectKind::Slow(0.5)));
+        assert_eq!(effects[0].remaining, 4.0, "新效果剩余时间更短时不应该把已有的更长剩余时间缩短");
+    }
+}
@@ -0,0 +1,104 @@
+// ==================== 回合调度系统 ====================
+
+/// 行动所需的能量阈值
+pub const ACTION_THRESHOLD: i32 = 100;
+/// 一次普通移动/攻击消耗的能量
+pub const ACTION_COST: i32 = 100;
+
+/// 参与回合调度的一个行动者（玩家或敌人）
+#[derive(Debug, Clone, Copy)]
+pub struct Actor {
+    pub id: usize,
+    pub energy: i32,
+    pub speed: i32,
+    pub is_player: bool,
+}
+
+impl Actor {
+    pub fn new(id: usize, speed: i32, is_player: bool) -> Self {
+        Self { id, energy: 0, speed, is_player }
+    }
+
+    pub fn can_act(&self) -> bool {
+        self.energy >= ACTION_THRESHOLD
+    }
+
+    pub fn spend(&mut self, cost: i32) {
+        self.energy -= cost;
+    }
+}
+
+/// 按发起顺序驱动行动者的调度器：每个tick给所有人加速度，
+/// 谁先攒够能量谁先行动；玩家回合会阻塞在输入上。
+pub struct Scheduler {
+    pub actors: Vec<Actor>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self { actors: Vec::new() }
+    }
+
+    pub fn add_actor(&mut self, actor: Actor) {
+        self.actors.push(actor);
+    }
+
+    pub fn remove_actor(&mut self, id: usize) {
+        self.actors.retain(|a| a.id != id);
+    }
+
+    /// 给所有行动者累加一次tick的能量
+    pub fn tick(&mut self) {
+        for actor in &mut self.actors {
+            actor.energy += actor.speed;
+        }
+    }
+
+    /// 返回下一个可以行动的行动者id：能量最高者优先，能量相同时取列表中靠后的那个
+    pub fn next_ready(&self) -> Option<usize> {
+        self.actors
+            .iter()
+            .filter(|a| a.can_act())
+            .max_by_key(|a| a.energy)
+            .map(|a| a.id)
+    }
+
+    pub fn spend(&mut self, id: usize, cost: i32) {
+        if let Some(actor) = self.actors.iter_mut().find(|a| a.id == id) {
+            actor.spend(cost);
+        }
+    }
+
+    pub fn is_player_turn(&self, id: usize) -> bool {
+        self.actors.iter().find(|a| a.id == id).map(|a| a.is_player).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn faster_actor_acts_more_often() {
+        let mut scheduler = Scheduler::new();
+        scheduler.add_actor(Actor::new(0, 100, true)); // 玩家：正常速度
+        scheduler.add_actor(Actor::new(1, 150, false)); // 敌人：更快
+
+        let mut player_actions = 0;
+        let mut enemy_actions = 0;
+
+        for _ in 0..10 {
+            scheduler.tick();
+            while let Some(id) = scheduler.next_ready() {
+                if id == 0 {
+                    player_actions += 1;
+                } else {
+                    enemy_actions += 1;
+                }
+                scheduler.spend(id, ACTION_COST);
+            }
+        }
+
+        assert!(enemy_actions > player_actions);
+    }
+}